@@ -1,9 +1,13 @@
 use anyhow::Result;
 use clap::Parser;
+use std::process::ExitCode;
 
 use super::subcommands::Subcommands;
 use crate::handlers::{cat, comm, cut, echo, find, grep, head, ls, tail, uniq, wc};
-use crate::utils::uniq_flags::UniqFlags;
+use crate::utils::{
+    binary_files::BinaryFiles, cat_flags::CatFlags, grep_flags::GrepFlags, head_flags::HeadFlags,
+    uniq_flags::UniqFlags, wc_flags::WcFlags,
+};
 
 #[derive(Parser)]
 #[clap(name = "unx")]
@@ -16,7 +20,7 @@ pub struct Cli {
 }
 
 impl Cli {
-    pub fn run() -> Result<()> {
+    pub fn run() -> Result<ExitCode> {
         let cli = Self::parse();
 
         match cli.subcommands {
@@ -26,11 +30,25 @@ impl Cli {
                 number_lines,
                 number_nonblank_lines,
                 squeeze_blank_lines,
+                show_all,
+                show_nonprinting_ends,
+                show_ends,
+                show_nonprinting_tabs,
+                show_tabs,
+                show_nonprinting,
             } => cat(
                 &files,
-                number_lines,
-                number_nonblank_lines,
-                squeeze_blank_lines,
+                &CatFlags {
+                    number_lines,
+                    number_nonblank_lines,
+                    squeeze_blank_lines,
+                    show_nonprinting: show_nonprinting
+                        || show_all
+                        || show_nonprinting_ends
+                        || show_nonprinting_tabs,
+                    show_ends: show_ends || show_all || show_nonprinting_ends,
+                    show_tabs: show_tabs || show_all || show_nonprinting_tabs,
+                },
             ),
             Subcommands::Head {
                 files,
@@ -38,14 +56,42 @@ impl Cli {
                 bytes,
                 quiet,
                 verbose,
-            } => head(&files, lines, bytes, quiet, verbose),
+                zero_terminated,
+                total,
+            } => head(
+                &files,
+                &HeadFlags {
+                    lines,
+                    bytes,
+                    quiet,
+                    verbose,
+                    zero_terminated,
+                    total,
+                },
+            ),
             Subcommands::Wc {
                 files,
                 lines,
                 words,
                 bytes,
                 chars,
-            } => wc(&files, lines, words, bytes, chars),
+                max_line_length,
+                files0_from,
+                total,
+                word_mode,
+            } => wc(
+                &files,
+                files0_from.as_deref(),
+                &WcFlags {
+                    lines,
+                    words,
+                    bytes,
+                    chars,
+                    max_line_length,
+                    total,
+                    word_mode,
+                },
+            ),
             Subcommands::Uniq {
                 in_file,
                 out_file,
@@ -79,11 +125,88 @@ impl Cli {
             Subcommands::Grep {
                 pattern,
                 files,
+                patterns,
+                pattern_files,
                 ignore_case,
+                fixed_strings,
+                perl_regexp,
+                word_regexp,
+                line_regexp,
+                multiline,
+                multiline_dotall,
                 recursive,
+                include,
+                exclude,
+                exclude_dir,
+                respect_ignore,
                 count,
                 invert,
-            } => grep(&pattern, &files, ignore_case, recursive, count, invert),
+                after_context,
+                before_context,
+                context,
+                line_number,
+                byte_offset,
+                with_filename,
+                no_filename,
+                color,
+                only_matching,
+                replace,
+                files_with_matches,
+                files_without_match,
+                quiet,
+                json,
+                max_count,
+                line_buffered,
+                binary_files,
+                text,
+                search_zip,
+                ..
+            } => {
+                return grep(
+                    pattern.as_deref(),
+                    &files,
+                    &GrepFlags {
+                        patterns,
+                        pattern_files,
+                        ignore_case,
+                        fixed_strings,
+                        perl_regexp,
+                        word_regexp,
+                        line_regexp,
+                        multiline,
+                        multiline_dotall,
+                        recursive,
+                        include,
+                        exclude,
+                        exclude_dir,
+                        respect_ignore,
+                        count,
+                        invert,
+                        after_context,
+                        before_context,
+                        context,
+                        line_number,
+                        byte_offset,
+                        with_filename,
+                        no_filename,
+                        color,
+                        only_matching,
+                        replace,
+                        files_with_matches,
+                        files_without_match,
+                        quiet,
+                        json,
+                        max_count,
+                        line_buffered,
+                        binary_files: if text {
+                            BinaryFiles::Text
+                        } else {
+                            binary_files
+                        },
+                        search_zip,
+                    },
+                )
+            }
             Subcommands::Comm {
                 file_1,
                 file_2,
@@ -114,6 +237,7 @@ impl Cli {
                 long,
                 show_hidden,
             } => ls(&paths, long, show_hidden),
-        }
+        }?;
+        Ok(ExitCode::SUCCESS)
     }
 }
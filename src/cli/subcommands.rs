@@ -1,8 +1,11 @@
-use clap::{ArgAction, Subcommand};
+use clap::{ArgAction, ColorChoice, Subcommand};
 use regex::Regex;
 
 use crate::constants::help_messages;
-use crate::utils::{entry_type::EntryType, extract::ArgsExtract};
+use crate::utils::{
+    binary_files::BinaryFiles, entry_type::EntryType, extract::ArgsExtract, head_count::HeadCount,
+    total_mode::TotalMode, word_mode::WordMode,
+};
 
 #[derive(Subcommand)]
 pub enum Subcommands {
@@ -31,6 +34,30 @@ pub enum Subcommands {
         /// suppress repeated empty output lines
         #[arg(short('s'), long("squeeze-blank"))]
         squeeze_blank_lines: bool,
+
+        /// equivalent to -vET
+        #[arg(short('A'), long)]
+        show_all: bool,
+
+        /// equivalent to -vE
+        #[arg(short('e'))]
+        show_nonprinting_ends: bool,
+
+        /// display $ at end of each line
+        #[arg(short('E'), long)]
+        show_ends: bool,
+
+        /// equivalent to -vT
+        #[arg(short('t'))]
+        show_nonprinting_tabs: bool,
+
+        /// display TAB characters as ^I
+        #[arg(short('T'), long)]
+        show_tabs: bool,
+
+        /// use ^ and M- notation, except for LFD and TAB
+        #[arg(short('v'), long)]
+        show_nonprinting: bool,
     },
 
     #[clap(about = help_messages::HEAD)]
@@ -38,25 +65,29 @@ pub enum Subcommands {
         #[arg(value_name = "FILE", default_value = "-")]
         files: Vec<String>,
 
-        /// print the first NUM lines instead of the first 10
+        /// print the first NUM lines instead of the first 10;
+        /// with the leading '-', print all but the last NUM lines of each file
         #[arg(
             short('n'),
             long,
             value_name = "LINES",
-            value_parser = clap::value_parser!(u64).range(1..),
+            allow_hyphen_values(true),
+            value_parser = clap::value_parser!(HeadCount),
             default_value = "10"
         )]
-        lines: u64,
+        lines: HeadCount,
 
-        /// print the first NUM bytes of each file
+        /// print the first NUM bytes of each file;
+        /// with the leading '-', print all but the last NUM bytes of each file
         #[arg(
             short('c'),
             long,
-            value_name = "BYTES", 
-            value_parser = clap::value_parser!(u64).range(1..),
+            value_name = "BYTES",
+            allow_hyphen_values(true),
+            value_parser = clap::value_parser!(HeadCount),
             conflicts_with("lines")
         )]
-        bytes: Option<u64>,
+        bytes: Option<HeadCount>,
 
         /// never print headers giving file names
         #[arg(short, long("quiet"), visible_alias = "silent")]
@@ -65,11 +96,19 @@ pub enum Subcommands {
         /// always print headers giving file names
         #[arg(short, long, conflicts_with("quiet"))]
         verbose: bool,
+
+        /// line delimiter is NUL, not newline
+        #[arg(short, long)]
+        zero_terminated: bool,
+
+        /// print NUM lines or bytes in total, counted across all files
+        #[arg(long)]
+        total: bool,
     },
 
     #[clap(about = help_messages::WC)]
     Wc {
-        #[arg(value_name = "FILE", default_value = "-")]
+        #[arg(value_name = "FILE", conflicts_with("files0_from"))]
         files: Vec<String>,
 
         /// print the newline counts
@@ -87,6 +126,23 @@ pub enum Subcommands {
         /// print the character counts
         #[arg(short('m'), long, conflicts_with("bytes"))]
         chars: bool,
+
+        /// print the maximum display width
+        #[arg(short('L'), long)]
+        max_line_length: bool,
+
+        /// read input from the files specified by NUL-terminated names in file F;
+        /// if F is - then read names from standard input
+        #[arg(long, value_name = "F")]
+        files0_from: Option<String>,
+
+        /// when to print a line with total counts; WHEN can be: auto, always, only, never
+        #[arg(long, value_name = "WHEN", default_value = "auto")]
+        total: TotalMode,
+
+        /// split words on ASCII whitespace, Unicode whitespace or UAX #29 word boundaries
+        #[arg(long, value_name = "MODE", default_value = "ascii")]
+        word_mode: WordMode,
     },
 
     #[clap(about=help_messages::UNIQ)]
@@ -173,22 +229,80 @@ pub enum Subcommands {
         extract: ArgsExtract,
     },
 
-    #[clap(about = help_messages::GREP)]
+    #[clap(about = help_messages::GREP, disable_help_flag = true)]
     Grep {
-        #[arg()]
-        pattern: String,
+        #[arg(required_unless_present_any(["patterns", "pattern_files"]))]
+        pattern: Option<String>,
 
-        #[arg(value_name = "FILE", default_value = "-")]
+        #[arg(value_name = "FILE")]
         files: Vec<String>,
 
+        /// use PATTERNS for matching
+        #[arg(
+            short('e'),
+            long("regexp"),
+            value_name = "PATTERNS",
+            action(ArgAction::Append)
+        )]
+        patterns: Vec<String>,
+
+        /// take PATTERNS from FILE
+        #[arg(
+            short('f'),
+            long("file"),
+            value_name = "FILE",
+            action(ArgAction::Append)
+        )]
+        pattern_files: Vec<String>,
+
         /// ignore case distinctions in patterns and data
         #[arg(short, long("ignore-case"))]
         ignore_case: bool,
 
+        /// PATTERNS are strings
+        #[arg(short('F'), long)]
+        fixed_strings: bool,
+
+        /// PATTERNS are Perl regular expressions
+        #[arg(short('P'), long, conflicts_with("fixed_strings"))]
+        perl_regexp: bool,
+
+        /// match only whole words
+        #[arg(short, long)]
+        word_regexp: bool,
+
+        /// match only whole lines
+        #[arg(short('x'), long)]
+        line_regexp: bool,
+
+        /// let matches span several lines
+        #[arg(short('U'), long)]
+        multiline: bool,
+
+        /// let '.' match newlines in multiline mode
+        #[arg(long, requires("multiline"))]
+        multiline_dotall: bool,
+
         /// handle directories recursively
         #[arg(short, long)]
         recursive: bool,
 
+        /// search only files that match GLOB
+        #[arg(long, value_name = "GLOB", action(ArgAction::Append))]
+        include: Vec<String>,
+
+        /// skip files that match GLOB
+        #[arg(long, value_name = "GLOB", action(ArgAction::Append))]
+        exclude: Vec<String>,
+
+        /// skip directories that match GLOB
+        #[arg(long, value_name = "GLOB", action(ArgAction::Append))]
+        exclude_dir: Vec<String>,
+
+        /// skip files listed in .gitignore and .ignore files
+        #[arg(long)]
+        respect_ignore: bool,
+
         /// print found matches count
         #[arg(short, long)]
         count: bool,
@@ -196,6 +310,102 @@ pub enum Subcommands {
         /// select non-matching lines
         #[arg(short('v'), long("invert-match"))]
         invert: bool,
+
+        /// print NUM lines of trailing context
+        #[arg(short('A'), long, value_name = "NUM")]
+        after_context: Option<usize>,
+
+        /// print NUM lines of leading context
+        #[arg(short('B'), long, value_name = "NUM")]
+        before_context: Option<usize>,
+
+        /// print NUM lines of output context
+        #[arg(short('C'), long, value_name = "NUM")]
+        context: Option<usize>,
+
+        /// print line number with output lines
+        #[arg(short('n'), long)]
+        line_number: bool,
+
+        /// print the byte offset with output lines
+        #[arg(short('b'), long)]
+        byte_offset: bool,
+
+        /// print file name with output lines
+        #[arg(short('H'), long, overrides_with("no_filename"))]
+        with_filename: bool,
+
+        /// suppress the file name prefix on output
+        #[arg(short('h'), long, overrides_with("with_filename"))]
+        no_filename: bool,
+
+        /// use markers to highlight the matching strings
+        #[arg(
+            long,
+            visible_alias = "colour",
+            value_name = "WHEN",
+            num_args(0..=1),
+            require_equals(true),
+            default_value = "never",
+            default_missing_value = "auto"
+        )]
+        color: ColorChoice,
+
+        /// show only nonempty parts of lines that match
+        #[arg(short('o'), long)]
+        only_matching: bool,
+
+        /// print matches replaced by TEMPLATE, which may refer to groups as $1 or ${name}
+        #[arg(long, value_name = "TEMPLATE")]
+        replace: Option<String>,
+
+        /// print only names of FILEs with selected lines
+        #[arg(short('l'), long, overrides_with("files_without_match"))]
+        files_with_matches: bool,
+
+        /// print only names of FILEs with no selected lines
+        #[arg(short('L'), long, overrides_with("files_with_matches"))]
+        files_without_match: bool,
+
+        /// suppress all normal output
+        #[arg(short, long, visible_alias = "silent")]
+        quiet: bool,
+
+        /// print results as JSON Lines
+        #[arg(
+            long,
+            conflicts_with_all(["count", "files_with_matches", "files_without_match", "quiet"])
+        )]
+        json: bool,
+
+        /// stop after NUM selected lines
+        #[arg(short('m'), long, value_name = "NUM")]
+        max_count: Option<usize>,
+
+        /// flush output on every line
+        #[arg(long)]
+        line_buffered: bool,
+
+        /// assume that binary files are TYPE; TYPE is 'binary', 'text', or 'without-match'
+        #[arg(
+            long,
+            value_name = "TYPE",
+            default_value = "binary",
+            overrides_with = "text"
+        )]
+        binary_files: BinaryFiles,
+
+        /// equivalent to --binary-files=text
+        #[arg(short('a'), long, overrides_with = "binary_files")]
+        text: bool,
+
+        /// search in gzip, bzip2 and xz compressed files
+        #[arg(short('z'), long)]
+        search_zip: bool,
+
+        /// Print help
+        #[arg(long, action(ArgAction::Help))]
+        help: Option<bool>,
     },
 
     #[clap(about = help_messages::COMM)]
@@ -10,7 +10,12 @@ With no FILE, or when FILE is -, read standard input.";
 pub const HEAD: &str = "Print the first 10 lines of each FILE to standard output.
 With more than one FILE, precede each with a header giving the file name.
 
-With no FILE, or when FILE is -, read standard input.";
+With no FILE, or when FILE is -, read standard input.
+
+NUM may have a multiplier suffix:
+b 512, kB 1000, K 1024, MB 1000*1000, M 1024*1024,
+GB 1000*1000*1000, G 1024*1024*1024, and so on for T, P, E.
+Binary prefixes can be used, too: KiB=K, MiB=M, and so on.";
 //--------------
 pub const WC: &str = "Print newline, word, and byte counts for each FILE, and a total line if
 more than one FILE is specified.  A word is a non-zero-length sequence of
@@ -1,54 +1,200 @@
 use anyhow::Result;
-use std::io::BufRead;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufWriter, ErrorKind, Write},
+};
 
-use super::helpers::{file_reader::open_file, logging::display_file_error};
+use super::helpers::{
+    file_reader::open_file,
+    logging::{display_file_error, is_broken_pipe},
+};
+use crate::utils::cat_flags::CatFlags;
 
-pub fn cat(
-    files: &[String],
-    number_lines: bool,
-    number_nonblank_lines: bool,
-    squeeze_blank_lines: bool,
-) -> Result<()> {
-    let mut line_num = 0;
+pub fn cat(files: &[String], flags: &CatFlags) -> Result<()> {
+    let is_formatted = flags.number_lines
+        || flags.number_nonblank_lines
+        || flags.squeeze_blank_lines
+        || flags.show_nonprinting
+        || flags.show_ends
+        || flags.show_tabs;
+    if !is_formatted {
+        return copy_files(files);
+    }
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let mut position = Position::new();
     for filename in files {
         match open_file(filename) {
             Err(e) => display_file_error("cat", filename, &e),
-            Ok(file) => {
-                handle_file(
-                    file,
-                    squeeze_blank_lines,
-                    number_lines,
-                    number_nonblank_lines,
-                    &mut line_num,
-                )?;
-            }
+            Ok(file) => match handle_file(file, flags, &mut position, &mut out) {
+                // like copy_files, only a reader that went away ends the run
+                Err(e) if is_broken_pipe(&e) => return Err(e),
+                Err(e) => display_file_error("cat", filename, &e),
+                Ok(()) => {}
+            },
+        }
+    }
+    if position.pending_cr {
+        write_content(&mut out, b"\r", flags)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Where the output stands, carried over from one file to the next the way GNU cat does:
+/// a file without a trailing newline leaves its last line open for the next file to finish,
+/// and a run of empty lines can continue into the next file
+struct Position {
+    line_num: u64,
+    /// empty lines in a row written just now, None while in the middle of a line
+    blank_run: Option<u8>,
+    /// a carriage return at the very end of a file, held back under -E in case a newline follows
+    pending_cr: bool,
+}
+
+impl Position {
+    fn new() -> Self {
+        Self {
+            line_num: 0,
+            blank_run: Some(0),
+            pending_cr: false,
+        }
+    }
+}
+
+/// Copies the files as they are, byte for byte.
+/// On Linux `io::copy` between a file and stdout goes through copy_file_range, sendfile or
+/// splice, so the data doesn't even pass through our own buffers
+fn copy_files(files: &[String]) -> Result<()> {
+    let mut out = io::stdout().lock();
+    for filename in files {
+        let copied = match filename.as_str() {
+            "-" => io::copy(&mut io::stdin().lock(), &mut out),
+            _ => File::open(filename).and_then(|mut file| io::copy(&mut file, &mut out)),
+        };
+        match copied {
+            // there is no point in going on once nobody reads the output
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => return Err(e.into()),
+            Err(e) => display_file_error("cat", filename, &e.into()),
+            Ok(_) => {}
         }
     }
     Ok(())
 }
 
 fn handle_file(
-    file: Box<dyn BufRead>,
-    squeeze_blank_lines: bool,
-    number_lines: bool,
-    number_nonblank_lines: bool,
-    line_num: &mut u64,
+    mut file: Box<dyn BufRead>,
+    flags: &CatFlags,
+    position: &mut Position,
+    out: &mut impl Write,
 ) -> Result<()> {
-    let mut prev_line = String::new();
-    for line_result in file.lines() {
-        let line = line_result?;
-        if squeeze_blank_lines && line.is_empty() && prev_line.is_empty() {
-            prev_line = line;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if file.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        // the last line keeps going without a newline when the file lacks one
+        let (mut content, has_newline) = match line.strip_suffix(b"\n") {
+            Some(content) => (content, true),
+            None => (&line[..], false),
+        };
+        // a carriage return left over from the previous file is part of this line's ending
+        // only if the line is nothing more than the newline
+        let pending_cr = std::mem::take(&mut position.pending_cr);
+        if pending_cr && !content.is_empty() {
+            write_content(out, b"\r", flags)?;
+        }
+
+        if content.is_empty() && has_newline {
+            match position.blank_run {
+                // the newline finishes a line the previous file left open
+                None => position.blank_run = Some(0),
+                Some(run) => {
+                    if flags.squeeze_blank_lines && run >= 1 {
+                        continue;
+                    }
+                    position.blank_run = Some(run.saturating_add(1));
+                    if flags.number_lines && !flags.number_nonblank_lines {
+                        write_line_num(out, position)?;
+                    }
+                }
+            }
+            if pending_cr {
+                out.write_all(b"^M")?;
+            }
+            write_line_end(out, flags)?;
             continue;
         }
 
-        if number_lines || (number_nonblank_lines && !line.is_empty()) {
-            *line_num += 1;
-            println!("{:6}\t{}", line_num, line);
+        if position.blank_run.is_some() && (flags.number_lines || flags.number_nonblank_lines) {
+            write_line_num(out, position)?;
+        }
+        // like GNU cat, -E shows the carriage return of a CRLF line ending even without -v
+        let ends_with_cr = flags.show_ends && content.ends_with(b"\r");
+        if ends_with_cr {
+            content = &content[..content.len() - 1];
+        }
+        write_content(out, content, flags)?;
+        if has_newline {
+            if ends_with_cr {
+                out.write_all(b"^M")?;
+            }
+            write_line_end(out, flags)?;
+            position.blank_run = Some(0);
         } else {
-            println!("{}", line);
+            position.pending_cr = ends_with_cr;
+            position.blank_run = None;
         }
-        prev_line = line;
     }
     Ok(())
 }
+
+fn write_line_num(out: &mut impl Write, position: &mut Position) -> Result<()> {
+    position.line_num += 1;
+    write!(out, "{:6}\t", position.line_num)?;
+    Ok(())
+}
+
+fn write_line_end(out: &mut impl Write, flags: &CatFlags) -> Result<()> {
+    if flags.show_ends {
+        out.write_all(b"$")?;
+    }
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+fn write_content(out: &mut impl Write, content: &[u8], flags: &CatFlags) -> Result<()> {
+    if flags.show_nonprinting || flags.show_tabs {
+        out.write_all(&make_visible(content, flags))?;
+    } else {
+        out.write_all(content)?;
+    }
+    Ok(())
+}
+
+/// Spells out control and non-ASCII bytes in ^ and M- notation, same as GNU cat
+fn make_visible(content: &[u8], flags: &CatFlags) -> Vec<u8> {
+    let mut visible = Vec::with_capacity(content.len());
+    for &byte in content {
+        match byte {
+            b'\t' if flags.show_tabs => visible.extend_from_slice(b"^I"),
+            b'\t' => visible.push(byte),
+            _ if !flags.show_nonprinting => visible.push(byte),
+            _ => {
+                let byte = if byte >= 128 {
+                    visible.extend_from_slice(b"M-");
+                    byte - 128
+                } else {
+                    byte
+                };
+                match byte {
+                    0..=31 => visible.extend_from_slice(&[b'^', byte + 64]),
+                    127 => visible.extend_from_slice(b"^?"),
+                    _ => visible.push(byte),
+                }
+            }
+        }
+    }
+    visible
+}
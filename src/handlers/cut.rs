@@ -75,20 +75,18 @@ fn parse_positions(range: String) -> Result<PositionList> {
             })
         })
         .collect::<Result<PositionList>>()
-        .map_err(From::from)
 }
 
 fn parse_index(input: &str) -> Result<usize> {
     let value_error = || anyhow!(r#"cut: invalid extract value: `{input}`"#);
-    input
-        .starts_with('+')
-        .then(|| Err(value_error()))
-        .unwrap_or_else(|| {
-            input
-                .parse::<NonZeroUsize>()
-                .map(|n| usize::from(n) - 1)
-                .map_err(|_| value_error())
-        })
+    if input.starts_with('+') {
+        Err(value_error())
+    } else {
+        input
+            .parse::<NonZeroUsize>()
+            .map(|n| usize::from(n) - 1)
+            .map_err(|_| value_error())
+    }
 }
 
 // -------------------
@@ -0,0 +1,101 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use std::{ops::Range, time::Duration};
+
+use super::searcher::Line;
+
+/// Counters reported at the end of each file and, added up, in the summary
+#[derive(Default, Clone, Copy)]
+pub struct Stats {
+    pub searches: usize,
+    pub searches_with_match: usize,
+    pub bytes_printed: usize,
+    pub matched_lines: usize,
+    pub matches: usize,
+}
+
+impl Stats {
+    pub fn add(&mut self, other: &Stats) {
+        self.searches += other.searches;
+        self.searches_with_match += other.searches_with_match;
+        self.bytes_printed += other.bytes_printed;
+        self.matched_lines += other.matched_lines;
+        self.matches += other.matches;
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "searches": self.searches,
+            "searches_with_match": self.searches_with_match,
+            "bytes_printed": self.bytes_printed,
+            "matched_lines": self.matched_lines,
+            "matches": self.matches,
+        })
+    }
+}
+
+pub fn begin(path: &str) -> Value {
+    json!({
+        "type": "begin",
+        "data": { "path": { "text": path } },
+    })
+}
+
+/// A selected line is reported as "match", a context line as "context"
+pub fn line(kind: &str, path: &str, line: &Line, submatches: &[Range<usize>]) -> Value {
+    let submatches: Vec<Value> = submatches
+        .iter()
+        .map(|m| {
+            json!({
+                "match": data(&line.value[m.clone()]),
+                "start": m.start,
+                "end": m.end,
+            })
+        })
+        .collect();
+    let mut text = line.value.clone();
+    text.push(b'\n');
+
+    json!({
+        "type": kind,
+        "data": {
+            "path": { "text": path },
+            "lines": data(&text),
+            "line_number": line.line_num,
+            "absolute_offset": line.byte_offset,
+            "submatches": submatches,
+        },
+    })
+}
+
+pub fn end(path: &str, stats: Stats) -> Value {
+    json!({
+        "type": "end",
+        "data": {
+            "path": { "text": path },
+            "stats": stats.to_json(),
+        },
+    })
+}
+
+pub fn summary(elapsed: Duration, stats: Stats) -> Value {
+    json!({
+        "type": "summary",
+        "data": {
+            "elapsed_total": {
+                "secs": elapsed.as_secs(),
+                "nanos": elapsed.subsec_nanos(),
+                "human": format!("{:.6}s", elapsed.as_secs_f64()),
+            },
+            "stats": stats.to_json(),
+        },
+    })
+}
+
+/// Text that isn't valid UTF-8 goes out base64 encoded, the same as in ripgrep
+fn data(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => json!({ "text": text }),
+        Err(_) => json!({ "bytes": STANDARD.encode(bytes) }),
+    }
+}
@@ -0,0 +1,255 @@
+use aho_corasick::{AhoCorasick, MatchKind};
+use anyhow::{anyhow, bail, Result};
+use regex::bytes::{Regex, RegexBuilder};
+use regex_syntax::{ast, Parser};
+use std::ops::Range;
+
+use crate::utils::grep_flags::GrepFlags;
+
+// empty character class, used when a pattern file holds no patterns at all
+const NO_MATCH: &str = r"[^\x00-\x{10FFFF}]";
+
+pub enum Matcher {
+    Regex(Regex),
+    Literal(AhoCorasick),
+    #[cfg(feature = "perl-regexp")]
+    Perl(fancy_regex::Regex),
+}
+
+impl Matcher {
+    pub fn new(patterns: &[String], flags: &GrepFlags) -> Result<Self> {
+        // a pattern spanning several lines stands for one pattern per line
+        let patterns: Vec<&str> = patterns.iter().flat_map(|val| val.split('\n')).collect();
+
+        if flags.perl_regexp {
+            return build_perl_pattern(&patterns, flags);
+        }
+        if flags.fixed_strings {
+            // the literal matcher knows neither word boundaries nor unicode case folding
+            let is_plain = !flags.word_regexp
+                && !flags.line_regexp
+                && (!flags.ignore_case || patterns.iter().all(|val| val.is_ascii()));
+            if is_plain {
+                let matcher = AhoCorasick::builder()
+                    .match_kind(MatchKind::LeftmostLongest)
+                    .ascii_case_insensitive(flags.ignore_case)
+                    .build(&patterns)
+                    .map_err(|e| anyhow!("grep: {e}"))?;
+                return Ok(Matcher::Literal(matcher));
+            }
+            let patterns: Vec<String> = patterns.iter().map(|val| regex::escape(val)).collect();
+            return build_regex_pattern(&patterns, flags).map(Matcher::Regex);
+        }
+
+        // each pattern is checked on its own so that none can unbalance the combined groups
+        for pattern in &patterns {
+            if let Err(e) = Parser::new().parse(pattern) {
+                match perl_only_syntax(&e) {
+                    Some(syntax) => {
+                        bail!("grep: invalid pattern `{pattern}`: {syntax} require -P")
+                    }
+                    None => bail!("grep: invalid pattern `{pattern}`"),
+                }
+            }
+        }
+        build_regex_pattern(&patterns, flags).map(Matcher::Regex)
+    }
+
+    /// Only the backtracking engine behind -P can fail, once a line exceeds its limit
+    pub fn is_match(&self, haystack: &[u8]) -> Result<bool> {
+        Ok(match self {
+            Matcher::Regex(pattern) => pattern.is_match(haystack),
+            Matcher::Literal(pattern) => pattern.is_match(haystack),
+            #[cfg(feature = "perl-regexp")]
+            Matcher::Perl(pattern) => pattern.is_match(haystack)?,
+        })
+    }
+
+    /// Spans of all non-empty matches, empty ones are of no use for printing
+    pub fn find_matches(&self, haystack: &[u8]) -> Result<Vec<Range<usize>>> {
+        let mut matches = self.find_all(haystack)?;
+        matches.retain(|m| !m.is_empty());
+        Ok(matches)
+    }
+
+    /// Non-empty matches along with what `template` expands to for each of them
+    pub fn replace_matches(
+        &self,
+        haystack: &[u8],
+        template: &[u8],
+    ) -> Result<Vec<(Range<usize>, Vec<u8>)>> {
+        let replacements: Vec<(Range<usize>, Vec<u8>)> = match self {
+            Matcher::Regex(pattern) => pattern
+                .captures_iter(haystack)
+                .map(|caps| {
+                    let replacement = expand(template, |group| {
+                        match group.parse() {
+                            Ok(index) => caps.get(index),
+                            Err(_) => caps.name(group),
+                        }
+                        .map(|m| m.as_bytes())
+                    });
+                    (caps.get(0).unwrap().range(), replacement)
+                })
+                .collect(),
+            Matcher::Literal(pattern) => pattern
+                .find_iter(haystack)
+                .map(|m| {
+                    let replacement = expand(template, |group| {
+                        (group == "0").then(|| &haystack[m.range()])
+                    });
+                    (m.range(), replacement)
+                })
+                .collect(),
+            #[cfg(feature = "perl-regexp")]
+            Matcher::Perl(pattern) => pattern
+                .captures_iter(haystack)
+                .map(|caps| {
+                    let caps = caps?;
+                    let replacement = expand(template, |group| {
+                        match group.parse() {
+                            Ok(index) => caps.get(index),
+                            Err(_) => caps.name(group),
+                        }
+                        .map(|m| m.as_bytes())
+                    });
+                    Ok((caps.get(0).unwrap().range(), replacement))
+                })
+                .collect::<Result<_>>()?,
+        };
+        Ok(replacements
+            .into_iter()
+            .filter(|(m, _)| !m.is_empty())
+            .collect())
+    }
+
+    pub fn find_all(&self, haystack: &[u8]) -> Result<Vec<Range<usize>>> {
+        Ok(match self {
+            Matcher::Regex(pattern) => pattern.find_iter(haystack).map(|m| m.range()).collect(),
+            Matcher::Literal(pattern) => pattern.find_iter(haystack).map(|m| m.range()).collect(),
+            #[cfg(feature = "perl-regexp")]
+            Matcher::Perl(pattern) => pattern
+                .find_iter(haystack)
+                .map(|m| Ok(m?.range()))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+fn build_regex_pattern<T: AsRef<str>>(patterns: &[T], flags: &GrepFlags) -> Result<Regex> {
+    let alternation = if patterns.is_empty() {
+        NO_MATCH.to_string()
+    } else {
+        patterns
+            .iter()
+            .map(|val| format!("(?:{})", val.as_ref()))
+            .collect::<Vec<_>>()
+            .join("|")
+    };
+    let alternation = if flags.line_regexp {
+        format!("^(?:{alternation})$")
+    } else if flags.word_regexp {
+        // unlike plain \b this also holds for patterns starting or ending with a non-word char
+        format!(r"\b{{start-half}}(?:{alternation})\b{{end-half}}")
+    } else {
+        alternation
+    };
+
+    let pattern = RegexBuilder::new(&alternation)
+        .case_insensitive(flags.ignore_case)
+        .multi_line(flags.multiline)
+        .dot_matches_new_line(flags.multiline_dotall)
+        .build()
+        .map_err(|e| anyhow!("grep: {e}"))?;
+    Ok(pattern)
+}
+
+/// Expands `$1`, `${1}`, `$name`, `${name}` and `$$` in `template` the same way as
+/// `regex::Captures::expand`, groups that took no part in the match expand to nothing
+fn expand<'h, F>(template: &[u8], group: F) -> Vec<u8>
+where
+    F: Fn(&str) -> Option<&'h [u8]>,
+{
+    let mut expanded = Vec::new();
+    let mut rest = template;
+    while let Some(dollar) = rest.iter().position(|&b| b == b'$') {
+        expanded.extend_from_slice(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+        if rest.first() == Some(&b'$') {
+            expanded.push(b'$');
+            rest = &rest[1..];
+            continue;
+        }
+        let (name, len) = if rest.first() == Some(&b'{') {
+            match rest.iter().position(|&b| b == b'}') {
+                Some(end) => (&rest[1..end], end + 1),
+                None => (&rest[..0], 0),
+            }
+        } else {
+            let end = rest
+                .iter()
+                .position(|&b| !(b.is_ascii_alphanumeric() || b == b'_'))
+                .unwrap_or(rest.len());
+            (&rest[..end], end)
+        };
+        // a `$` that doesn't start a group reference is kept as it is
+        if name.is_empty() {
+            expanded.push(b'$');
+            continue;
+        }
+        if let Some(value) = std::str::from_utf8(name).ok().and_then(&group) {
+            expanded.extend_from_slice(value);
+        }
+        rest = &rest[len..];
+    }
+    expanded.extend_from_slice(rest);
+    expanded
+}
+
+/// Names the syntax that only the backtracking engine behind -P understands
+fn perl_only_syntax(error: &regex_syntax::Error) -> Option<&'static str> {
+    match error {
+        regex_syntax::Error::Parse(e) => match e.kind() {
+            ast::ErrorKind::UnsupportedLookAround => Some("look-around assertions"),
+            ast::ErrorKind::UnsupportedBackreference => Some("backreferences"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(feature = "perl-regexp")]
+fn build_perl_pattern(patterns: &[&str], flags: &GrepFlags) -> Result<Matcher> {
+    use fancy_regex::{BytesMode, RegexBuilder};
+
+    // several patterns can't be joined into one without renumbering their backreferences
+    let pattern = match patterns {
+        [] => NO_MATCH,
+        [pattern] => pattern,
+        _ => bail!("grep: the -P option only supports a single pattern"),
+    };
+    let pattern = if flags.line_regexp {
+        format!("^(?:{pattern})$")
+    } else if flags.word_regexp {
+        format!(r"(?<!\w)(?:{pattern})(?!\w)")
+    } else {
+        pattern.to_string()
+    };
+    let pattern = match (flags.multiline, flags.multiline_dotall) {
+        (true, true) => format!("(?ms){pattern}"),
+        (true, false) => format!("(?m){pattern}"),
+        _ => pattern,
+    };
+
+    let pattern = RegexBuilder::new(&pattern)
+        .case_insensitive(flags.ignore_case)
+        .bytes_mode(BytesMode::UnicodeBytes)
+        .build()
+        .map_err(|e| anyhow!("grep: {e}"))?;
+    Ok(Matcher::Perl(pattern))
+}
+
+#[cfg(not(feature = "perl-regexp"))]
+fn build_perl_pattern(_patterns: &[&str], _flags: &GrepFlags) -> Result<Matcher> {
+    bail!("grep: Perl matching not supported, unx was built without the `perl-regexp` feature")
+}
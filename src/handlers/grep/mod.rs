@@ -0,0 +1,280 @@
+mod json;
+mod matcher;
+mod printer;
+mod searcher;
+mod walker;
+
+use anyhow::{anyhow, bail, Result};
+use std::{
+    fs,
+    io::BufRead,
+    num::NonZeroUsize,
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Mutex,
+    },
+    thread,
+};
+
+use super::helpers::{
+    file_reader::{open_file, open_file_decompressed, open_file_map_err},
+    logging::{display_error, display_file_error, is_broken_pipe},
+};
+use crate::utils::grep_flags::GrepFlags;
+use matcher::Matcher;
+use printer::Printer;
+use searcher::{Line, Output, Searcher};
+use walker::FileFilter;
+
+const ERROR_STATUS: u8 = 2;
+/// How many outputs a file that is ahead of the one being printed can hold back
+const PENDING_OUTPUTS: usize = 1024;
+
+/// Outcome of searching a single file, or the error that kept it from being found
+type FileResult<T> = Result<(String, Result<T>)>;
+
+/// What a worker found in a file, passed on to the printer
+enum Event {
+    Output(Output<Line>),
+    Done(Result<usize>),
+}
+
+#[derive(Default)]
+struct Status {
+    any_selected: bool,
+    has_errors: bool,
+}
+
+pub fn grep(pattern: Option<&str>, files: &[String], flags: &GrepFlags) -> Result<ExitCode> {
+    // GNU grep reserves status 1 for "no lines selected", so fatal errors map to 2 as well
+    match search(pattern, files, flags) {
+        Ok(status) => Ok(status),
+        // the reader went away, e.g. `unx grep ... | head`
+        Err(e) if is_broken_pipe(&e) => Ok(ExitCode::SUCCESS),
+        Err(e) => {
+            eprintln!("{e}");
+            Ok(ExitCode::from(ERROR_STATUS))
+        }
+    }
+}
+
+fn search(pattern: Option<&str>, files: &[String], flags: &GrepFlags) -> Result<ExitCode> {
+    let mut files = files.to_vec();
+    let patterns = if flags.patterns.is_empty() && flags.pattern_files.is_empty() {
+        pattern.map(str::to_string).into_iter().collect()
+    } else {
+        // with -e or -f the first positional argument is a file, not a pattern
+        files.splice(0..0, pattern.map(str::to_string));
+        collect_patterns(&flags.patterns, &flags.pattern_files)?
+    };
+    // like GNU grep, a recursive search without a file searches the working directory,
+    // naming the files found there without a leading "./"
+    let is_implicit_dir = files.is_empty() && flags.recursive;
+    if files.is_empty() {
+        files.push(if flags.recursive { "." } else { "-" }.to_string());
+    }
+
+    let matcher = Matcher::new(&patterns, flags)?;
+    let filter = FileFilter::new(flags)?;
+    // a directory is named in the output even if it holds a single file
+    let with_filename = flags.with_filename
+        || !flags.no_filename
+            && (files.len() > 1 || flags.recursive && files.iter().any(|f| is_dir(f)));
+    let searcher = Searcher::new(&matcher, flags);
+    let mut printer = Printer::new(flags, &matcher, with_filename);
+
+    let entries = walker::walk(&files, flags, &filter).map(|entry| {
+        entry.map(|path| match path.strip_prefix("./") {
+            Some(path) if is_implicit_dir => path.to_string(),
+            _ => path,
+        })
+    });
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    // line buffering asks for every line as soon as it is found, not once its file is done
+    let status = if flags.recursive && !flags.line_buffered && threads > 1 {
+        search_parallel(entries, &searcher, &mut printer, flags, threads)?
+    } else {
+        search_sequential(entries, &searcher, &mut printer, flags)?
+    };
+    printer.finish()?;
+
+    // a selected line wins over errors only when output is suppressed anyway
+    Ok(
+        if status.has_errors && !(flags.quiet && status.any_selected) {
+            ExitCode::from(ERROR_STATUS)
+        } else if status.any_selected {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        },
+    )
+}
+
+fn search_sequential(
+    entries: impl Iterator<Item = Result<String>>,
+    searcher: &Searcher,
+    printer: &mut Printer,
+    flags: &GrepFlags,
+) -> Result<Status> {
+    let mut status = Status::default();
+    for entry in entries {
+        let result = entry.map(|filename| {
+            let file_name = display_name(&filename);
+            let count = search_file(searcher, flags, &filename, |output| {
+                printer.log_output(file_name, output)
+            });
+            (filename, count)
+        });
+        report(printer, &mut status, result)?;
+        if flags.quiet && status.any_selected {
+            break;
+        }
+    }
+    Ok(status)
+}
+
+/// Searches files on worker threads while printing the results in the same order as a
+/// sequential search would: the file that is next in line is printed as it is searched,
+/// files ahead of it buffer at most `PENDING_OUTPUTS` outputs each before their worker waits.
+/// The files are still listed by a single walker, whose order is what keeps the output the
+/// same from one run to the next
+fn search_parallel(
+    entries: impl Iterator<Item = Result<String>> + Send,
+    searcher: &Searcher,
+    printer: &mut Printer,
+    flags: &GrepFlags,
+    threads: usize,
+) -> Result<Status> {
+    let entries = Mutex::new(entries);
+    let is_done = AtomicBool::new(false);
+    // files are handed to the printer in the order they were taken from the walker
+    let (sender, receiver) = mpsc::sync_channel(threads);
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let (entries, is_done, sender) = (&entries, &is_done, sender.clone());
+            scope.spawn(move || {
+                while !is_done.load(Ordering::Relaxed) {
+                    let mut entries = entries.lock().unwrap();
+                    let Some(entry) = entries.next() else {
+                        break;
+                    };
+                    // a file is handed over before the walker is unlocked, so that the files
+                    // reach the printer in the order they were listed; the receiver only hangs
+                    // up once the search has come to an end
+                    let filename = match entry {
+                        Err(e) => {
+                            if sender.send(Err(e)).is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                        Ok(filename) => filename,
+                    };
+                    let (events, receiver) = mpsc::sync_channel(PENDING_OUTPUTS);
+                    if sender.send(Ok((filename.clone(), receiver))).is_err() {
+                        break;
+                    }
+                    drop(entries);
+
+                    let count = search_file(searcher, flags, &filename, |output| {
+                        events
+                            .send(Event::Output(output.cloned()))
+                            .map_err(|_| anyhow!("grep: {filename}: nothing to print to"))
+                    });
+                    // nobody is waiting for the count of a file whose printing failed
+                    let _ = events.send(Event::Done(count));
+                }
+            });
+        }
+        drop(sender);
+
+        let mut status = Status::default();
+        for file in receiver {
+            let result = file.map(|(filename, events)| {
+                let count = log_events(printer, &filename, events);
+                (filename, count)
+            });
+            if let Err(e) = report(printer, &mut status, result) {
+                is_done.store(true, Ordering::Relaxed);
+                return Err(e);
+            }
+            if flags.quiet && status.any_selected {
+                is_done.store(true, Ordering::Relaxed);
+                return Ok(status);
+            }
+        }
+        Ok(status)
+    })
+}
+
+fn search_file<F>(searcher: &Searcher, flags: &GrepFlags, filename: &str, log: F) -> Result<usize>
+where
+    F: FnMut(Output<&Line>) -> Result<()>,
+{
+    let file = if flags.search_zip {
+        open_file_decompressed(filename)?
+    } else {
+        open_file(filename)?
+    };
+    searcher.search(file, log)
+}
+
+/// Prints the outputs of a file as they come in, until its worker is done with it
+fn log_events(printer: &mut Printer, filename: &str, events: Receiver<Event>) -> Result<usize> {
+    for event in events {
+        match event {
+            Event::Output(output) => {
+                printer.log_output(display_name(filename), output.borrowed())?
+            }
+            Event::Done(count) => return count,
+        }
+    }
+    // a worker only leaves a file unfinished when it panics
+    bail!("grep: {filename}: search was interrupted")
+}
+
+/// Prints the per-file summary or error and keeps track of the exit status
+fn report(printer: &mut Printer, status: &mut Status, result: FileResult<usize>) -> Result<()> {
+    match result {
+        Err(e) => {
+            display_error("grep", &e);
+            status.has_errors = true;
+        }
+        // failing to write the output is fatal, unlike failing to read a file
+        Ok((_, Err(e))) if is_broken_pipe(&e) => return Err(e),
+        Ok((filename, Err(e))) => {
+            display_file_error("grep", &filename, &e);
+            status.has_errors = true;
+        }
+        Ok((filename, Ok(count))) => {
+            status.any_selected |= count > 0;
+            printer.log_summary(display_name(&filename), count)?;
+        }
+    }
+    Ok(())
+}
+
+//------------------
+fn collect_patterns(patterns: &[String], pattern_files: &[String]) -> Result<Vec<String>> {
+    let mut results = patterns.to_vec();
+    for filename in pattern_files {
+        for line in open_file_map_err(filename, "grep")?.lines() {
+            results.push(line?);
+        }
+    }
+    Ok(results)
+}
+
+fn display_name(filename: &str) -> &str {
+    match filename {
+        "-" => "(standard input)",
+        _ => filename,
+    }
+}
+
+fn is_dir(path: &str) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| metadata.is_dir())
+}
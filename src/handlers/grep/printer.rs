@@ -0,0 +1,339 @@
+use anyhow::Result;
+use clap::ColorChoice;
+use serde_json::Value;
+use std::{
+    env,
+    io::{self, BufWriter, IsTerminal, StdoutLock, Write},
+    mem,
+    ops::Range,
+    time::Instant,
+};
+
+use super::{
+    json::{self, Stats},
+    matcher::Matcher,
+    searcher::{Line, Output},
+};
+use crate::utils::{grep_colors::GrepColors, grep_flags::GrepFlags};
+
+const GROUP_SEPARATOR: &str = "--";
+
+/// Where the --json output stands
+struct JsonLog {
+    started: Instant,
+    has_begun: bool,
+    file: Stats,
+    total: Stats,
+}
+
+pub struct Printer<'a> {
+    flags: &'a GrepFlags,
+    matcher: &'a Matcher,
+    colors: Option<GrepColors>,
+    with_filename: bool,
+    line_buffered: bool,
+    has_output: bool,
+    json: Option<JsonLog>,
+    out: BufWriter<StdoutLock<'static>>,
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(flags: &'a GrepFlags, matcher: &'a Matcher, with_filename: bool) -> Self {
+        let stdout = io::stdout();
+        Self {
+            flags,
+            matcher,
+            colors: use_colors(flags.color).then(GrepColors::from_env),
+            with_filename,
+            // a terminal expects to see each line as soon as it is found
+            line_buffered: flags.line_buffered || stdout.is_terminal(),
+            has_output: false,
+            json: flags.json.then(|| JsonLog {
+                started: Instant::now(),
+                has_begun: false,
+                file: Stats::default(),
+                total: Stats::default(),
+            }),
+            out: BufWriter::new(stdout.lock()),
+        }
+    }
+
+    pub fn log_output(&mut self, file_name: &str, output: Output<&Line>) -> Result<()> {
+        let flags = self.flags;
+        if flags.quiet || flags.files_with_matches || flags.files_without_match || flags.count {
+            return Ok(());
+        }
+        if self.json.is_some() {
+            return self.log_json(file_name, output);
+        }
+        match output {
+            Output::BinaryMatch => {
+                self.has_output = true;
+                self.write_line(format!("Binary file {file_name} matches").as_bytes())
+            }
+            Output::Selected(line) => self.log_line(self.file_name(file_name), line, true),
+            Output::Context(line) => self.log_line(self.file_name(file_name), line, false),
+            // groups are separated across files as well, but nothing precedes the first one
+            Output::Separator if !self.has_output => Ok(()),
+            Output::Separator => {
+                let separator = self.paint(|c| &c.separator, GROUP_SEPARATOR);
+                self.write_line(separator.as_bytes())
+            }
+        }
+    }
+
+    /// Per-file output of the modes which only report once a file has been searched
+    pub fn log_summary(&mut self, file_name: &str, count: usize) -> Result<()> {
+        let flags = self.flags;
+        if flags.quiet {
+            return Ok(());
+        }
+        if self.json.is_some() {
+            self.log_json_end(file_name, count)?;
+        } else if flags.files_with_matches || flags.files_without_match {
+            if (count > 0) == flags.files_with_matches {
+                let file_name = self.paint(|c| &c.file_name, file_name);
+                self.write_line(file_name.as_bytes())?;
+            }
+        } else if flags.count {
+            let prefix = if self.with_filename {
+                self.paint(|c| &c.file_name, file_name) + &self.paint(|c| &c.separator, ":")
+            } else {
+                String::new()
+            };
+            self.write_line(format!("{prefix}{count}").as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Writes out whatever is left, along with the --json summary
+    pub fn finish(&mut self) -> Result<()> {
+        if let Some(log) = &self.json {
+            let summary = json::summary(log.started.elapsed(), log.total);
+            self.write_json(summary)?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+
+    fn log_json(&mut self, file_name: &str, output: Output<&Line>) -> Result<()> {
+        let (event, matches) = match output {
+            Output::Separator => return Ok(()),
+            // the lines of a binary file are counted but never shown
+            Output::BinaryMatch => (None, Vec::new()),
+            Output::Selected(line) => {
+                let matches = if self.flags.invert {
+                    Vec::new()
+                } else {
+                    self.find_matches(line)?
+                };
+                (
+                    Some(json::line("match", file_name, line, &matches)),
+                    matches,
+                )
+            }
+            Output::Context(line) => (
+                Some(json::line("context", file_name, line, &[])),
+                Vec::new(),
+            ),
+        };
+
+        let Some(log) = &mut self.json else {
+            return Ok(());
+        };
+        if !matches!(output, Output::Context(_)) {
+            log.file.matched_lines += 1;
+            log.file.matches += matches.len();
+        }
+        if !log.has_begun {
+            log.has_begun = true;
+            self.write_json(json::begin(file_name))?;
+        }
+        match event {
+            Some(event) => self.write_json(event),
+            None => Ok(()),
+        }
+    }
+
+    fn log_json_end(&mut self, file_name: &str, count: usize) -> Result<()> {
+        let Some(log) = &mut self.json else {
+            return Ok(());
+        };
+        log.file.searches = 1;
+        log.file.searches_with_match = usize::from(count > 0);
+        // files without a single line to show aren't mentioned at all
+        let end = mem::take(&mut log.has_begun).then(|| json::end(file_name, log.file).to_string());
+        if let Some(end) = &end {
+            log.file.bytes_printed += end.len() + 1;
+        }
+        log.total.add(&mem::take(&mut log.file));
+        match end {
+            Some(end) => self.write_line(end.as_bytes()),
+            None => Ok(()),
+        }
+    }
+
+    fn write_json(&mut self, event: Value) -> Result<()> {
+        let line = event.to_string();
+        if let Some(log) = &mut self.json {
+            log.file.bytes_printed += line.len() + 1;
+        }
+        self.write_line(line.as_bytes())
+    }
+
+    fn log_line(&mut self, file_name: Option<&str>, line: &Line, is_selected: bool) -> Result<()> {
+        self.has_output = true;
+        if self.flags.only_matching {
+            // with -v the selected lines hold no matches, context lines are never shown
+            if is_selected && !self.flags.invert {
+                self.log_matches(file_name, line)?;
+            }
+            return Ok(());
+        }
+        let prefix = self.prefix(file_name, line.line_num, line.byte_offset, is_selected);
+        let value = match &self.colors {
+            Some(colors) => self.highlight(colors, line, is_selected)?,
+            None if self.replaces(is_selected) => self.replace(line)?,
+            None => line.value.to_vec(),
+        };
+        self.write_line(&[prefix.as_bytes(), &value].concat())
+    }
+
+    fn log_matches(&mut self, file_name: Option<&str>, line: &Line) -> Result<()> {
+        for (m, text) in self.segments(line, true)? {
+            let prefix = self.prefix(file_name, line.line_num, line.byte_offset + m.start, true);
+            let value = match &self.colors {
+                Some(colors) => colors.paint_bytes(&colors.selected_match, &text),
+                None => text,
+            };
+            self.write_line(&[prefix.as_bytes(), &value].concat())?;
+        }
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> Result<()> {
+        self.out.write_all(line)?;
+        self.out.write_all(b"\n")?;
+        if self.line_buffered {
+            self.out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Matches of `line`, each along with the text printed in its place
+    fn segments(&self, line: &Line, is_selected: bool) -> Result<Vec<(Range<usize>, Vec<u8>)>> {
+        match &self.flags.replace {
+            Some(template) if self.replaces(is_selected) => self
+                .matcher
+                .replace_matches(&line.value, template.as_bytes()),
+            _ => Ok(self
+                .find_matches(line)?
+                .into_iter()
+                .map(|m| (m.clone(), line.value[m].to_vec()))
+                .collect()),
+        }
+    }
+
+    /// Only the matches of selected lines are replaced, never those shown as context
+    fn replaces(&self, is_selected: bool) -> bool {
+        self.flags.replace.is_some() && is_selected && !self.flags.invert
+    }
+
+    fn replace(&self, line: &Line) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        let mut last_end = 0;
+        for (m, text) in self.segments(line, true)? {
+            output.extend_from_slice(&line.value[last_end..m.start]);
+            output.extend(text);
+            last_end = m.end;
+        }
+        output.extend_from_slice(&line.value[last_end..]);
+        Ok(output)
+    }
+
+    fn find_matches(&self, line: &Line) -> Result<Vec<Range<usize>>> {
+        match &line.matches {
+            Some(matches) => Ok(matches.clone()),
+            None => self.matcher.find_matches(&line.value),
+        }
+    }
+
+    fn file_name<'f>(&self, file_name: &'f str) -> Option<&'f str> {
+        self.with_filename.then_some(file_name)
+    }
+
+    fn prefix(
+        &self,
+        file_name: Option<&str>,
+        line_num: usize,
+        byte_offset: usize,
+        is_selected: bool,
+    ) -> String {
+        let separator = self.paint(|c| &c.separator, if is_selected { ":" } else { "-" });
+        let mut prefix = String::new();
+        if let Some(file_name) = file_name {
+            prefix += &(self.paint(|c| &c.file_name, file_name) + &separator);
+        }
+        if self.flags.line_number {
+            let line_num = line_num.to_string();
+            prefix += &(self.paint(|c| &c.line_number, &line_num) + &separator);
+        }
+        if self.flags.byte_offset {
+            let byte_offset = byte_offset.to_string();
+            prefix += &(self.paint(|c| &c.byte_offset, &byte_offset) + &separator);
+        }
+        prefix
+    }
+
+    fn paint(&self, sgr: fn(&GrepColors) -> &String, text: &str) -> String {
+        match &self.colors {
+            Some(colors) => colors.paint(sgr(colors), text),
+            None => text.to_string(),
+        }
+    }
+
+    fn highlight(&self, colors: &GrepColors, line: &Line, is_selected: bool) -> Result<Vec<u8>> {
+        let (mut line_color, match_color) = if is_selected {
+            (&colors.selected_line, &colors.selected_match)
+        } else {
+            (&colors.context_line, &colors.context_match)
+        };
+        if colors.reverse && self.flags.invert {
+            line_color = if is_selected {
+                &colors.context_line
+            } else {
+                &colors.selected_line
+            };
+        }
+
+        let mut output = Vec::new();
+        let mut last_end = 0;
+        // with -v the matches live in context lines rather than in selected ones
+        let shows_matches = !match_color.is_empty() || self.replaces(is_selected);
+        if is_selected ^ self.flags.invert && shows_matches {
+            for (m, text) in self.segments(line, is_selected)? {
+                output.extend_from_slice(colors.start(line_color).as_bytes());
+                output.extend_from_slice(&line.value[last_end..m.start]);
+                output.extend(colors.paint_bytes(match_color, &text));
+                last_end = m.end;
+            }
+        }
+        let tail = &line.value[last_end..];
+        if !tail.is_empty() {
+            output.extend(colors.paint_bytes(line_color, tail));
+        }
+        Ok(output)
+    }
+}
+
+fn use_colors(color: ColorChoice) -> bool {
+    match color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            io::stdout().is_terminal()
+                && env::var_os("NO_COLOR").is_none_or(|val| val.is_empty())
+                && env::var_os("TERM").is_some_and(|term| term != "dumb")
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use anyhow::Result;
+use std::{collections::VecDeque, io::BufRead, iter, ops::Range};
+
+use super::matcher::Matcher;
+use crate::utils::{binary_files::BinaryFiles, grep_flags::GrepFlags};
+
+#[derive(Clone)]
+pub struct Line {
+    pub value: Vec<u8>,
+    pub line_num: usize,
+    pub byte_offset: usize,
+    /// parts of the line taken by a match when it is known up front, as in multiline mode
+    pub matches: Option<Vec<Range<usize>>>,
+}
+
+pub enum Output<L> {
+    Selected(L),
+    Context(L),
+    Separator,
+    /// stands in for the lines of a binary file, which are never printed
+    BinaryMatch,
+}
+
+impl Output<&Line> {
+    pub fn cloned(&self) -> Output<Line> {
+        match self {
+            Output::Selected(line) => Output::Selected(Line::clone(line)),
+            Output::Context(line) => Output::Context(Line::clone(line)),
+            Output::Separator => Output::Separator,
+            Output::BinaryMatch => Output::BinaryMatch,
+        }
+    }
+}
+
+impl Output<Line> {
+    pub fn borrowed(&self) -> Output<&Line> {
+        match self {
+            Output::Selected(line) => Output::Selected(line),
+            Output::Context(line) => Output::Context(line),
+            Output::Separator => Output::Separator,
+            Output::BinaryMatch => Output::BinaryMatch,
+        }
+    }
+}
+
+pub struct Searcher<'a> {
+    matcher: &'a Matcher,
+    invert: bool,
+    max_count: Option<usize>,
+    before_context: usize,
+    after_context: usize,
+    show_separator: bool,
+    shows_lines: bool,
+    binary_files: BinaryFiles,
+    multiline: bool,
+}
+
+impl<'a> Searcher<'a> {
+    pub fn new(matcher: &'a Matcher, flags: &GrepFlags) -> Self {
+        // explicit -A/-B take precedence over -C regardless of their order
+        let before_context = flags.before_context.or(flags.context);
+        let after_context = flags.after_context.or(flags.context);
+        // listing modes only need to know whether a file has a single selected line
+        let is_listing = flags.quiet || flags.files_with_matches || flags.files_without_match;
+        let shows_lines = !is_listing && !flags.count;
+
+        Self {
+            matcher,
+            invert: flags.invert,
+            max_count: if is_listing { Some(1) } else { flags.max_count },
+            before_context: before_context.filter(|_| shows_lines).unwrap_or(0),
+            after_context: after_context.filter(|_| shows_lines).unwrap_or(0),
+            show_separator: before_context.is_some() || after_context.is_some(),
+            shows_lines,
+            binary_files: flags.binary_files,
+            multiline: flags.multiline,
+        }
+    }
+
+    /// Passes selected lines of `file`, along with their context, to `log` as soon as they
+    /// are read and returns how many lines were selected.
+    /// Every group of lines is preceded by a separator when context is requested, it is up to
+    /// the printer to drop the one in front of the very first group
+    pub fn search<F>(&self, mut file: Box<dyn BufRead>, log: F) -> Result<usize>
+    where
+        F: FnMut(Output<&Line>) -> Result<()>,
+    {
+        // like GNU grep, only a NUL byte in the first block makes a file binary
+        let is_binary = self.binary_files != BinaryFiles::Text && file.fill_buf()?.contains(&0);
+        if is_binary && self.binary_files == BinaryFiles::WithoutMatch {
+            return Ok(0);
+        }
+        let hides_lines = is_binary && self.shows_lines;
+
+        if self.multiline {
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)?;
+            let lines = self.split_matched_lines(&buffer)?.into_iter().map(Ok);
+            self.select(lines, hides_lines, log)
+        } else {
+            let lines = read_lines(file).map(|line| {
+                let line = line?;
+                let is_match = self.matcher.is_match(&line.value)?;
+                Ok((line, is_match))
+            });
+            self.select(lines, hides_lines, log)
+        }
+    }
+
+    fn select<I, F>(&self, lines: I, hides_lines: bool, mut log: F) -> Result<usize>
+    where
+        I: Iterator<Item = Result<(Line, bool)>>,
+        F: FnMut(Output<&Line>) -> Result<()>,
+    {
+        // lines seen since the last printed one, kept in case a match follows
+        let mut before_lines = VecDeque::with_capacity(self.before_context);
+        let mut after_left = 0;
+        let mut last_printed: Option<usize> = None;
+        let mut selected_count = 0;
+
+        for line in lines {
+            let (line, is_match) = line?;
+            let line_num = line.line_num;
+            let is_exhausted = self.max_count.is_some_and(|max| selected_count >= max);
+            let is_selected = !is_exhausted && is_match ^ self.invert;
+
+            if is_selected && hides_lines {
+                log(Output::BinaryMatch)?;
+                return Ok(1);
+            } else if is_selected {
+                let group_start = line_num - before_lines.len();
+                let is_new_group = last_printed.is_none_or(|last| group_start > last + 1);
+                if self.show_separator && is_new_group {
+                    log(Output::Separator)?;
+                }
+                for line in before_lines.drain(..) {
+                    log(Output::Context(&line))?;
+                }
+                log(Output::Selected(&line))?;
+                selected_count += 1;
+                last_printed = Some(line_num);
+                after_left = self.after_context;
+            } else if after_left > 0 {
+                log(Output::Context(&line))?;
+                last_printed = Some(line_num);
+                after_left -= 1;
+            } else if self.before_context > 0 {
+                if before_lines.len() == self.before_context {
+                    before_lines.pop_front();
+                }
+                before_lines.push_back(line);
+            }
+
+            let is_exhausted = self.max_count.is_some_and(|max| selected_count >= max);
+            // trailing context of the last selected line is still printed
+            if is_exhausted && after_left == 0 {
+                break;
+            }
+        }
+        Ok(selected_count)
+    }
+
+    /// Splits `buffer` into lines, marking every line that takes part in a match of the
+    /// whole buffer and keeping the part of each match which falls within the line
+    fn split_matched_lines(&self, buffer: &[u8]) -> Result<Vec<(Line, bool)>> {
+        let spans = self.matcher.find_all(buffer)?;
+        // the last byte of a match, an empty one still belongs to the line it's found on
+        let last_byte = |span: &Range<usize>| span.end.max(span.start + 1) - 1;
+
+        let mut lines = Vec::new();
+        let mut first_span = 0;
+        let mut start = 0;
+        for (index, value) in buffer.split_inclusive(|&b| b == b'\n').enumerate() {
+            let end = start + value.len();
+            let value = value.strip_suffix(b"\n").unwrap_or(value);
+            while spans
+                .get(first_span)
+                .is_some_and(|span| last_byte(span) < start)
+            {
+                first_span += 1;
+            }
+            let line_spans: Vec<&Range<usize>> = spans[first_span..]
+                .iter()
+                .take_while(|span| span.start < end)
+                .collect();
+            let matches = line_spans
+                .iter()
+                .map(|span| {
+                    span.start.max(start) - start..span.end.min(start + value.len()) - start
+                })
+                .filter(|m| !m.is_empty())
+                .collect();
+
+            lines.push((
+                Line {
+                    value: value.to_vec(),
+                    line_num: index + 1,
+                    byte_offset: start,
+                    matches: Some(matches),
+                },
+                !line_spans.is_empty(),
+            ));
+            start = end;
+        }
+        Ok(lines)
+    }
+}
+
+fn read_lines(mut file: Box<dyn BufRead>) -> impl Iterator<Item = Result<Line>> {
+    let mut line_num = 0;
+    let mut byte_offset = 0;
+    iter::from_fn(move || {
+        let mut value = Vec::new();
+        match file.read_until(b'\n', &mut value) {
+            Err(e) => Some(Err(e.into())),
+            Ok(0) => None,
+            Ok(bytes) => {
+                if value.ends_with(b"\n") {
+                    value.pop();
+                }
+                line_num += 1;
+                let line = Line {
+                    value,
+                    line_num,
+                    byte_offset,
+                    matches: None,
+                };
+                byte_offset += bytes;
+                Some(Ok(line))
+            }
+        }
+    })
+}
@@ -0,0 +1,112 @@
+use anyhow::{anyhow, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::{fs, iter};
+
+use crate::utils::grep_flags::GrepFlags;
+
+/// The --include, --exclude and --exclude-dir globs
+#[derive(Clone)]
+pub struct FileFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    exclude_dir: GlobSet,
+}
+
+impl FileFilter {
+    pub fn new(flags: &GrepFlags) -> Result<Self> {
+        Ok(Self {
+            include: (!flags.include.is_empty())
+                .then(|| build_glob_set(&flags.include))
+                .transpose()?,
+            exclude: build_glob_set(&flags.exclude)?,
+            exclude_dir: build_glob_set(&flags.exclude_dir)?,
+        })
+    }
+
+    fn is_file_excluded(&self, path: &str) -> bool {
+        self.include.as_ref().is_some_and(|set| !matches(set, path)) || matches(&self.exclude, path)
+    }
+
+    fn is_dir_excluded(&self, path: &str) -> bool {
+        matches(&self.exclude_dir, path)
+    }
+}
+
+fn build_glob_set(globs: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).map_err(|e| anyhow!("grep: {e}"))?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Like GNU grep a glob may match either the whole path or any suffix of it that starts
+/// right after a slash, so `--exclude=*.txt` and `--exclude=dir/*.txt` both work
+fn matches(set: &GlobSet, path: &str) -> bool {
+    let suffixes = path
+        .match_indices('/')
+        .map(|(i, _)| &path[i + 1..])
+        .filter(|suffix| !suffix.is_empty() && !suffix.starts_with('/'));
+    iter::once(path).chain(suffixes).any(|s| set.is_match(s))
+}
+
+/// Lazily lists the files to search in the order of `paths`, descending into directories
+/// when searching recursively; nothing is read before the caller asks for the next file
+pub fn walk<'a>(
+    paths: &'a [String],
+    flags: &'a GrepFlags,
+    filter: &'a FileFilter,
+) -> impl Iterator<Item = Result<String>> + Send + 'a {
+    paths.iter().flat_map(
+        move |path| -> Box<dyn Iterator<Item = Result<String>> + Send + 'a> {
+            if path == "-" {
+                return Box::new(iter::once(Ok(path.to_string())));
+            }
+            match fs::metadata(path) {
+                Err(e) => Box::new(iter::once(Err(anyhow!("{path}: {e}")))),
+                Ok(metadata) if metadata.is_dir() => {
+                    if !flags.recursive {
+                        Box::new(iter::once(Err(anyhow!("{path} is a directory"))))
+                    } else if filter.is_dir_excluded(path) {
+                        Box::new(iter::empty())
+                    } else {
+                        Box::new(walk_dir(path, flags.respect_ignore, filter))
+                    }
+                }
+                Ok(_) if filter.is_file_excluded(path) => Box::new(iter::empty()),
+                Ok(_) => Box::new(iter::once(Ok(path.to_string()))),
+            }
+        },
+    )
+}
+
+fn walk_dir<'a>(
+    path: &str,
+    respect_ignore: bool,
+    filter: &'a FileFilter,
+) -> impl Iterator<Item = Result<String>> + Send + 'a {
+    let dir_filter = filter.clone();
+    WalkBuilder::new(path)
+        // GNU grep searches hidden files too, ignore files are only read on request
+        .standard_filters(false)
+        .ignore(respect_ignore)
+        .git_ignore(respect_ignore)
+        .git_exclude(respect_ignore)
+        .parents(respect_ignore)
+        .require_git(false)
+        .filter_entry(move |entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_some_and(|t| t.is_dir())
+                || !dir_filter.is_dir_excluded(&entry.path().display().to_string())
+        })
+        .build()
+        .filter_map(move |entry| match entry {
+            Err(e) => Some(Err(From::from(e))),
+            Ok(entry) if entry.file_type().is_some_and(|t| t.is_file()) => {
+                let path = entry.path().display().to_string();
+                (!filter.is_file_excluded(&path)).then_some(Ok(path))
+            }
+            Ok(_) => None,
+        })
+}
@@ -1,32 +1,45 @@
-use anyhow::Result;
-use std::io::BufRead;
+use anyhow::{bail, Result};
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, Read, Write},
+};
 
 use super::helpers::{
     file_reader::open_file,
     logging::{display_file_error, display_file_header},
 };
+use crate::utils::{head_count::HeadCount, head_flags::HeadFlags};
 
-pub fn head(
-    files: &[String],
-    lines: u64,
-    bytes: Option<u64>,
-    quiet: bool,
-    verbose: bool,
-) -> Result<()> {
+pub fn head(files: &[String], flags: &HeadFlags) -> Result<()> {
+    // with --total the count is a budget shared by all files, used up as they get printed
+    let mut budget = match (flags.total, flags.bytes.unwrap_or(flags.lines)) {
+        (false, _) => None,
+        (true, HeadCount::First(num)) => Some(num),
+        (true, HeadCount::AllButLast(_)) => {
+            bail!("head: --total can't be combined with a negative count")
+        }
+    };
     for (file_num, filename) in files.iter().enumerate() {
+        let file_flags = match budget {
+            Some(0) => break,
+            Some(left) if flags.bytes.is_some() => HeadFlags {
+                bytes: Some(HeadCount::First(left)),
+                ..*flags
+            },
+            Some(left) => HeadFlags {
+                bytes: None,
+                lines: HeadCount::First(left),
+                ..*flags
+            },
+            None => *flags,
+        };
         match open_file(filename) {
             Err(e) => display_file_error("head", filename, &e),
             Ok(file) => {
-                handle_file(
-                    file,
-                    filename,
-                    files.len(),
-                    file_num,
-                    bytes,
-                    lines,
-                    quiet,
-                    verbose,
-                )?;
+                let printed = handle_file(file, filename, files.len(), file_num, &file_flags)?;
+                if let Some(left) = budget.as_mut() {
+                    *left -= printed;
+                }
             }
         }
     }
@@ -34,31 +47,102 @@ pub fn head(
 }
 
 fn handle_file(
-    mut file: Box<dyn BufRead>,
+    file: Box<dyn BufRead>,
     filename: &str,
     files_count: usize,
     file_num: usize,
-    bytes: Option<u64>,
-    lines: u64,
-    quiet: bool,
-    verbose: bool,
-) -> Result<()> {
-    display_file_header(filename, quiet, verbose, files_count, file_num);
+    flags: &HeadFlags,
+) -> Result<u64> {
+    display_file_header(filename, flags.quiet, flags.verbose, files_count, file_num);
 
-    if let Some(num_bytes) = bytes {
-        let mut buffer = vec![0; num_bytes as usize];
-        let bytes_read = file.read(&mut buffer)?;
-        print!("{}", String::from_utf8_lossy(&buffer[..bytes_read]));
-    } else {
-        let mut line = String::new();
-        for _ in 0..lines {
-            let bytes = file.read_line(&mut line)?;
-            if bytes == 0 {
-                break;
+    let delimiter = if flags.zero_terminated { b'\0' } else { b'\n' };
+    // only the first lines or bytes count towards the --total budget
+    let printed = match (flags.bytes, flags.lines) {
+        (Some(HeadCount::First(num_bytes)), _) => print_first_bytes(file, num_bytes)?,
+        (Some(HeadCount::AllButLast(num_bytes)), _) => {
+            print_all_but_last_bytes(file, num_bytes)?;
+            0
+        }
+        (None, HeadCount::First(num_lines)) => print_first_lines(file, num_lines, delimiter)?,
+        (None, HeadCount::AllButLast(num_lines)) => {
+            print_all_but_last_lines(file, num_lines, delimiter)?;
+            0
+        }
+    };
+    Ok(printed)
+}
+
+/// A single read may return less than asked for, so keep copying until the count is reached
+fn print_first_bytes(file: Box<dyn BufRead>, num_bytes: u64) -> Result<u64> {
+    let mut out = io::stdout().lock();
+    let printed = io::copy(&mut file.take(num_bytes), &mut out)?;
+    out.flush()?;
+    Ok(printed)
+}
+
+fn print_first_lines(mut file: Box<dyn BufRead>, num_lines: u64, delimiter: u8) -> Result<u64> {
+    let mut out = io::stdout().lock();
+    let mut line = Vec::new();
+    let mut printed = 0;
+    while printed < num_lines {
+        if file.read_until(delimiter, &mut line)? == 0 {
+            break;
+        }
+        out.write_all(&line)?;
+        line.clear();
+        printed += 1;
+    }
+    out.flush()?;
+    Ok(printed)
+}
+
+/// Holds back the last `num_bytes` bytes read in a ring buffer, so that streams whose size
+/// isn't known up front can be handled too
+fn print_all_but_last_bytes(mut file: Box<dyn BufRead>, num_bytes: u64) -> Result<()> {
+    let mut out = io::stdout().lock();
+    let mut ring = VecDeque::new();
+    loop {
+        let buffer = file.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        let read = buffer.len();
+        ring.extend(buffer);
+        file.consume(read);
+
+        let excess = ring.len().saturating_sub(num_bytes as usize);
+        if excess > 0 {
+            let (front, back) = ring.as_slices();
+            let front_len = front.len().min(excess);
+            out.write_all(&front[..front_len])?;
+            out.write_all(&back[..excess - front_len])?;
+            ring.drain(..excess);
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Same as above, only the ring buffer keeps whole lines
+fn print_all_but_last_lines(
+    mut file: Box<dyn BufRead>,
+    num_lines: u64,
+    delimiter: u8,
+) -> Result<()> {
+    let mut out = io::stdout().lock();
+    let mut ring = VecDeque::new();
+    loop {
+        let mut line = Vec::new();
+        if file.read_until(delimiter, &mut line)? == 0 {
+            break;
+        }
+        ring.push_back(line);
+        if ring.len() as u64 > num_lines {
+            if let Some(line) = ring.pop_front() {
+                out.write_all(&line)?;
             }
-            print!("{line}");
-            line.clear();
         }
     }
+    out.flush()?;
     Ok(())
 }
@@ -1,8 +1,15 @@
 use anyhow::{anyhow, Result};
+use bzip2::bufread::MultiBzDecoder;
+use flate2::bufread::MultiGzDecoder;
 use std::{
     fs::File,
     io::{self, BufRead, BufReader},
 };
+use xz2::bufread::XzDecoder;
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const BZIP2_MAGIC: &[u8] = b"BZh";
+const XZ_MAGIC: &[u8] = b"\xfd7zXZ\x00";
 
 pub fn open_file(filename: &str) -> Result<Box<dyn BufRead>> {
     match filename {
@@ -19,3 +26,22 @@ pub fn open_file_map_err(filename: &str, subcommand: &str) -> Result<Box<dyn Buf
         ))),
     }
 }
+
+/// Same as `open_file`, but gzip, bzip2 and xz data is decompressed on the fly.
+/// The format is told by the magic bytes at the start, whatever the file is named
+pub fn open_file_decompressed(filename: &str) -> Result<Box<dyn BufRead>> {
+    decompress(open_file(filename)?)
+}
+
+pub fn decompress(mut reader: Box<dyn BufRead>) -> Result<Box<dyn BufRead>> {
+    let header = reader.fill_buf()?;
+    Ok(if header.starts_with(GZIP_MAGIC) {
+        Box::new(BufReader::new(MultiGzDecoder::new(reader)))
+    } else if header.starts_with(BZIP2_MAGIC) {
+        Box::new(BufReader::new(MultiBzDecoder::new(reader)))
+    } else if header.starts_with(XZ_MAGIC) {
+        Box::new(BufReader::new(XzDecoder::new_multi_decoder(reader)))
+    } else {
+        reader
+    })
+}
@@ -1,4 +1,5 @@
 use anyhow::Error;
+use std::io;
 
 pub fn display_error(subcommand: &str, error: &Error) {
     eprintln!("{subcommand}: {error}");
@@ -8,6 +9,13 @@ pub fn display_file_error(subcommand: &str, filename: &str, error: &Error) {
     eprintln!("{subcommand}: {filename}: {error}");
 }
 
+/// Whoever reads the output went away, there is no point in going on
+pub fn is_broken_pipe(error: &Error) -> bool {
+    error
+        .downcast_ref::<io::Error>()
+        .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+}
+
 pub fn display_file_header(
     filename: &str,
     quiet: bool,
@@ -19,7 +27,11 @@ pub fn display_file_header(
         println!(
             "{}==> {} <==",
             if file_num > 0 { "\n" } else { "" },
-            filename
+            if filename == "-" {
+                "standard input"
+            } else {
+                filename
+            }
         );
     }
 }
@@ -29,9 +29,9 @@ fn find_files(paths: &[String], show_hidden: bool) -> Result<Vec<PathBuf>> {
                 if metadata.is_dir() {
                     for entry in fs::read_dir(path)? {
                         let path = entry?.path();
-                        let is_hidden = path.file_name().map_or(false, |file_name| {
-                            file_name.to_string_lossy().starts_with('.')
-                        });
+                        let is_hidden = path
+                            .file_name()
+                            .is_some_and(|file_name| file_name.to_string_lossy().starts_with('.'));
                         if !is_hidden || show_hidden {
                             results.push(path);
                         }
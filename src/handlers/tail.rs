@@ -43,6 +43,7 @@ pub fn tail(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_file(
     file: File,
     filename: &str,
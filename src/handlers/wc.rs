@@ -1,165 +1,487 @@
-use anyhow::Result;
-use std::io::BufRead;
+use anyhow::{anyhow, Result};
+use memchr::{memchr, memchr_iter};
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom},
+    num::NonZeroUsize,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
 
-use super::helpers::{file_reader::open_file, logging::display_file_error};
+use super::helpers::{
+    file_reader::open_file,
+    logging::{display_error, display_file_error},
+};
+use crate::utils::{total_mode::TotalMode, wc_flags::WcFlags, word_mode::WordMode};
 
-#[derive(Debug, PartialEq)]
+const TAB_WIDTH: usize = 8;
+const BLOCK_SIZE: usize = 256 * 1024;
+/// Regular files bigger than this are scanned in chunks of this size, on as many threads
+/// as there are cores
+const CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Default, PartialEq)]
 struct FileData {
     num_lines: usize,
     num_words: usize,
     num_bytes: usize,
     num_chars: usize,
+    max_line_length: usize,
+}
+
+impl FileData {
+    fn add(&mut self, other: &FileData) {
+        self.num_lines += other.num_lines;
+        self.num_words += other.num_words;
+        self.num_bytes += other.num_bytes;
+        self.num_chars += other.num_chars;
+        self.max_line_length = self.max_line_length.max(other.max_line_length);
+    }
 }
 
 //----------------------
-pub fn wc(
-    files: &[String],
-    mut lines: bool,
-    mut words: bool,
-    mut bytes: bool,
-    chars: bool,
-) -> Result<()> {
-    adjust_flags(&mut lines, &mut words, &mut bytes, chars)?;
-
-    let mut total_lines = 0;
-    let mut total_words = 0;
-    let mut total_bytes = 0;
-    let mut total_chars = 0;
-    for filename in files {
-        match open_file(filename) {
-            Err(e) => display_file_error("wc", filename, &e),
-            Ok(file) => {
-                handle_file(
-                    file,
-                    lines,
-                    words,
-                    bytes,
-                    chars,
-                    filename,
-                    &mut total_lines,
-                    &mut total_words,
-                    &mut total_bytes,
-                    &mut total_chars,
-                )?;
+pub fn wc(files: &[String], files0_from: Option<&str>, flags: &WcFlags) -> Result<()> {
+    let flags = adjust_flags(flags);
+    // only stdin read for want of any file goes without a name
+    let (files, named) = match files0_from {
+        Some(list) => (read_file_names(list)?, true),
+        None if files.is_empty() => (vec!["-".to_string()], false),
+        None => (files.to_vec(), true),
+    };
+    let width = number_width(&files, &flags);
+
+    let mut total = FileData::default();
+    for (i, filename) in files.iter().enumerate() {
+        if let Some(list) = files0_from {
+            if filename.is_empty() {
+                display_error(
+                    "wc",
+                    &anyhow!("{list}:{}: invalid zero-length file name", i + 1),
+                );
+                continue;
+            }
+            if list == "-" && filename == "-" {
+                display_error(
+                    "wc",
+                    &anyhow!("when reading file names from stdin, no file name of '-' allowed"),
+                );
+                continue;
             }
         }
+        let name = if named {
+            format!(" {filename}")
+        } else {
+            "".to_string()
+        };
+        handle_file(&flags, filename, &name, width, &mut total);
     }
-    if files.len() > 1 {
-        display_totals(
-            lines,
-            words,
-            bytes,
-            chars,
-            total_lines,
-            total_words,
-            total_bytes,
-            total_chars,
-        )?;
+    match flags.total {
+        TotalMode::Always => display_counts(&total, &flags, width, " total"),
+        TotalMode::Auto if files.len() > 1 => display_counts(&total, &flags, width, " total"),
+        TotalMode::Only => display_counts(&total, &flags, width, ""),
+        _ => {}
     }
     Ok(())
 }
 
+/// Reads the NUL-terminated names of --files0-from
+fn read_file_names(list: &str) -> Result<Vec<String>> {
+    let names = match list {
+        "-" => {
+            let mut names = Vec::new();
+            io::stdin().read_to_end(&mut names)?;
+            names
+        }
+        _ => fs::read(list).map_err(|e| anyhow!("wc: cannot open '{list}' for reading: {e}"))?,
+    };
+    let names = names.strip_suffix(b"\0").unwrap_or(&names);
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(names
+        .split(|&byte| byte == b'\0')
+        .map(|name| String::from_utf8_lossy(name).to_string())
+        .collect())
+}
+
+/// Same as GNU wc, the columns are as wide as the byte total of the regular files, standard
+/// input included, which no count can exceed, so they line up before anything is read; any
+/// other input makes them at least 7 wide, while a single count of a single file isn't padded
+/// at all
+fn number_width(files: &[String], flags: &WcFlags) -> usize {
+    let num_counts = [
+        flags.lines,
+        flags.words,
+        flags.bytes,
+        flags.chars,
+        flags.max_line_length,
+    ]
+    .iter()
+    .filter(|val| **val)
+    .count();
+    if files.len() == 1 && num_counts == 1 {
+        return 1;
+    }
+
+    let mut min_width = 1;
+    let mut size_total: u64 = 0;
+    for filename in files {
+        let metadata = if filename == "-" {
+            stdin_metadata()
+        } else {
+            fs::metadata(filename)
+        };
+        match metadata {
+            Ok(metadata) if metadata.is_file() => size_total += metadata.len(),
+            Ok(_) => min_width = 7,
+            Err(_) if filename == "-" => min_width = 7,
+            Err(_) => {}
+        }
+    }
+    size_total.to_string().len().max(min_width)
+}
+
+/// fstat on standard input, which is a regular file when it is redirected from one
+#[cfg(unix)]
+fn stdin_metadata() -> io::Result<fs::Metadata> {
+    use std::os::fd::AsFd;
+    File::from(io::stdin().as_fd().try_clone_to_owned()?).metadata()
+}
+
+#[cfg(not(unix))]
+fn stdin_metadata() -> io::Result<fs::Metadata> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
 //----------------------
-fn adjust_flags(lines: &mut bool, words: &mut bool, bytes: &mut bool, chars: bool) -> Result<()> {
-    if [*lines, *words, *bytes, chars]
-        .iter()
-        .all(|val| val == &false)
+fn adjust_flags(flags: &WcFlags) -> WcFlags {
+    if [
+        flags.lines,
+        flags.words,
+        flags.bytes,
+        flags.chars,
+        flags.max_line_length,
+    ]
+    .iter()
+    .all(|val| val == &false)
     {
-        *lines = true;
-        *words = true;
-        *bytes = true;
+        return WcFlags {
+            lines: true,
+            words: true,
+            bytes: true,
+            ..*flags
+        };
     }
-    Ok(())
+    *flags
 }
 
-fn handle_file(
-    file: Box<dyn BufRead>,
-    lines: bool,
-    words: bool,
-    bytes: bool,
-    chars: bool,
-    filename: &str,
-    total_lines: &mut usize,
-    total_words: &mut usize,
-    total_bytes: &mut usize,
-    total_chars: &mut usize,
-) -> Result<()> {
-    if let Ok(data) = count(file) {
-        println!(
-            "{}{}{}{}{}",
-            format_field(data.num_lines, lines),
-            format_field(data.num_words, words),
-            format_field(data.num_bytes, bytes),
-            format_field(data.num_chars, chars),
-            format_filename(filename)
-        );
-        *total_lines += data.num_lines;
-        *total_words += data.num_words;
-        *total_bytes += data.num_bytes;
-        *total_chars += data.num_chars;
+fn handle_file(flags: &WcFlags, filename: &str, name: &str, width: usize, total: &mut FileData) {
+    match count_file(filename, flags) {
+        Err(e) => display_file_error("wc", filename, &e),
+        Ok(data) => {
+            if flags.total != TotalMode::Only {
+                display_counts(&data, flags, width, name);
+            }
+            total.add(&data);
+        }
+    }
+}
+
+/// Picks the cheapest way to get the requested counts: the size of a regular file is known
+/// without reading it, newlines are found with memchr, and only words, characters and line
+/// widths need a full scan, which is split among threads for big files
+fn count_file(filename: &str, flags: &WcFlags) -> Result<FileData> {
+    let needs_scan = flags.words || flags.chars || flags.max_line_length;
+    if filename != "-" {
+        let metadata = fs::metadata(filename)?;
+        if metadata.is_file() {
+            if !needs_scan && !flags.lines {
+                return count_bytes(File::open(filename)?, metadata.len());
+            }
+            if needs_scan && metadata.len() > CHUNK_SIZE {
+                return scan_in_chunks(filename, metadata.len(), flags.word_mode);
+            }
+        }
+    }
+    let file = open_file(filename)?;
+    if needs_scan {
+        scan(file, flags.word_mode)
+    } else {
+        count_lines(file)
     }
-    Ok(())
 }
 
-fn count(mut file: Box<dyn BufRead>) -> Result<FileData> {
-    let mut num_lines = 0;
-    let mut num_words = 0;
-    let mut num_bytes = 0;
-    let mut num_chars = 0;
-    let mut line = String::new();
+/// Like GNU wc, trusts the size only up to the last block and reads the rest,
+/// since files in /proc or /sys may claim any size
+fn count_bytes(mut file: File, size: u64) -> Result<FileData> {
+    let skipped = size - size % (BLOCK_SIZE as u64 + 1);
+    file.seek(SeekFrom::Start(skipped))?;
+    let rest = io::copy(&mut file, &mut io::sink())?;
+    Ok(FileData {
+        num_bytes: (skipped + rest) as usize,
+        ..Default::default()
+    })
+}
 
+fn count_lines(mut file: impl Read) -> Result<FileData> {
+    let mut data = FileData::default();
+    let mut block = vec![0; BLOCK_SIZE];
     loop {
-        let line_bytes = file.read_line(&mut line)?;
-        if line_bytes == 0 {
-            break;
+        let read = match file.read(&mut block) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        data.num_lines += memchr_iter(b'\n', &block[..read]).count();
+        data.num_bytes += read;
+    }
+    Ok(data)
+}
+
+fn scan(mut file: impl Read, word_mode: WordMode) -> Result<FileData> {
+    let mut scanner = Scanner::new(word_mode);
+    let mut block = vec![0; BLOCK_SIZE];
+    loop {
+        match file.read(&mut block) {
+            Ok(0) => break,
+            Ok(read) => scanner.feed(&block[..read]),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
         }
-        num_lines += 1;
-        num_words += line.split_ascii_whitespace().count();
-        num_bytes += line_bytes;
-        num_chars += line.chars().count();
+    }
+    Ok(scanner.finish())
+}
 
-        line.clear();
+/// Every chunk takes the lines that start in it, so a scan always begins at the start of a line
+/// and the counts of the chunks simply add up
+fn scan_in_chunks(filename: &str, size: u64, word_mode: WordMode) -> Result<FileData> {
+    let num_chunks = size.div_ceil(CHUNK_SIZE) as usize;
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(num_chunks);
+    let next_chunk = AtomicUsize::new(0);
+
+    let results: Vec<Result<FileData>> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut data = FileData::default();
+                    loop {
+                        let chunk = next_chunk.fetch_add(1, Ordering::Relaxed) as u64;
+                        if chunk >= num_chunks as u64 {
+                            return Ok(data);
+                        }
+                        // the last chunk reads on in case the file has grown
+                        let end = match chunk + 1 {
+                            next if next == num_chunks as u64 => u64::MAX,
+                            next => next * CHUNK_SIZE,
+                        };
+                        data.add(&scan_chunk(filename, chunk * CHUNK_SIZE, end, word_mode)?);
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("wc: worker thread panicked"))
+            .collect()
+    });
+
+    let mut data = FileData::default();
+    for result in results {
+        data.add(&result?);
     }
-    Ok(FileData {
-        num_lines,
-        num_words,
-        num_bytes,
-        num_chars,
-    })
+    Ok(data)
 }
 
-fn display_totals(
-    lines: bool,
-    words: bool,
-    bytes: bool,
-    chars: bool,
-    total_lines: usize,
-    total_words: usize,
-    total_bytes: usize,
-    total_chars: usize,
-) -> Result<()> {
-    println!(
-        "{}{}{}{} total",
-        format_field(total_lines, lines),
-        format_field(total_words, words),
-        format_field(total_bytes, bytes),
-        format_field(total_chars, chars)
-    );
-    Ok(())
+fn scan_chunk(filename: &str, start: u64, end: u64, word_mode: WordMode) -> Result<FileData> {
+    let mut file = BufReader::with_capacity(BLOCK_SIZE, File::open(filename)?);
+    let mut pos = start;
+    if start > 0 {
+        // the line that runs over the start belongs to the previous chunk
+        file.seek(SeekFrom::Start(start - 1))?;
+        pos = start - 1 + file.skip_until(b'\n')? as u64;
+    }
+    let mut scanner = Scanner::new(word_mode);
+    if pos >= end {
+        return Ok(scanner.finish());
+    }
+    loop {
+        let buffer = file.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        // once at the end of the chunk, only the rest of the current line belongs to it
+        let search_from = end.saturating_sub(1).saturating_sub(pos);
+        if search_from < buffer.len() as u64 {
+            let search_from = search_from as usize;
+            if let Some(i) = memchr(b'\n', &buffer[search_from..]) {
+                scanner.feed(&buffer[..=search_from + i]);
+                break;
+            }
+        }
+        let read = buffer.len();
+        scanner.feed(buffer);
+        pos += read as u64;
+        file.consume(read);
+    }
+    Ok(scanner.finish())
 }
 
-fn format_field(value: usize, flag: bool) -> String {
-    if flag {
-        format!("{value:>8}")
-    } else {
-        "".to_string()
+/// Goes through the raw bytes, decoding UTF-8 along the way for the character count and line
+/// widths. As in GNU wc, invalid sequences still count as bytes but neither as characters nor
+/// as part of a word
+struct Scanner {
+    data: FileData,
+    decoder: Utf8Decoder,
+    word_mode: WordMode,
+    in_word: bool,
+    line_length: usize,
+    /// the text of the current line, kept only to find its UAX #29 words once it's complete;
+    /// no word goes past a line break
+    line: String,
+}
+
+impl Scanner {
+    fn new(word_mode: WordMode) -> Self {
+        Self {
+            data: FileData::default(),
+            decoder: Utf8Decoder::default(),
+            word_mode,
+            in_word: false,
+            line_length: 0,
+            line: String::new(),
+        }
+    }
+
+    fn feed(&mut self, block: &[u8]) {
+        for &byte in block {
+            let Some(ch) = self.decoder.push(byte) else {
+                continue;
+            };
+            self.data.num_chars += 1;
+            match ch {
+                '\n' | '\r' | '\x0c' => {
+                    if ch == '\n' {
+                        self.data.num_lines += 1;
+                    }
+                    self.end_line();
+                }
+                '\t' => self.line_length += TAB_WIDTH - self.line_length % TAB_WIDTH,
+                _ => self.line_length += ch.width().unwrap_or(0),
+            }
+            let is_space = match self.word_mode {
+                // the set C isspace knows, which unlike is_ascii_whitespace includes \v
+                WordMode::Ascii => matches!(ch, ' ' | '\t'..='\r'),
+                WordMode::Unicode => ch.is_whitespace(),
+                WordMode::Uax29 => {
+                    // the line breaks were just dealt with by end_line
+                    if !matches!(ch, '\n' | '\r' | '\x0c') {
+                        self.line.push(ch);
+                    }
+                    continue;
+                }
+            };
+            if is_space {
+                self.in_word = false;
+            } else if !self.in_word {
+                self.in_word = true;
+                self.data.num_words += 1;
+            }
+        }
+        self.data.num_bytes += block.len();
+    }
+
+    fn end_line(&mut self) {
+        self.data.max_line_length = self.data.max_line_length.max(self.line_length);
+        self.line_length = 0;
+        if !self.line.is_empty() {
+            self.data.num_words += self.line.unicode_words().count();
+            self.line.clear();
+        }
+    }
+
+    fn finish(mut self) -> FileData {
+        self.end_line();
+        self.data
     }
 }
 
-fn format_filename(filename: &str) -> String {
-    if filename == "-" {
-        "".to_string()
-    } else {
-        format!(" {filename}")
+/// Decodes UTF-8 a byte at a time, so a character may be split between two reads
+#[derive(Default)]
+struct Utf8Decoder {
+    code_point: u32,
+    needed: u8,
+    lower: u8,
+    upper: u8,
+}
+
+impl Utf8Decoder {
+    /// Returns the character the byte completes, if any
+    fn push(&mut self, byte: u8) -> Option<char> {
+        if self.needed > 0 {
+            if (self.lower..=self.upper).contains(&byte) {
+                self.code_point = (self.code_point << 6) | u32::from(byte & 0x3f);
+                self.lower = 0x80;
+                self.upper = 0xbf;
+                self.needed -= 1;
+                return match self.needed {
+                    0 => char::from_u32(self.code_point),
+                    _ => None,
+                };
+            }
+            // the sequence is cut short, but the byte may well start the next one
+            self.needed = 0;
+        }
+        // the bounds of the second byte rule out overlong forms, surrogates and values
+        // past U+10FFFF
+        self.lower = 0x80;
+        self.upper = 0xbf;
+        match byte {
+            0x00..=0x7f => return Some(char::from(byte)),
+            0xc2..=0xdf => {
+                self.needed = 1;
+                self.code_point = u32::from(byte & 0x1f);
+            }
+            0xe0..=0xef => {
+                self.needed = 2;
+                self.code_point = u32::from(byte & 0x0f);
+                match byte {
+                    0xe0 => self.lower = 0xa0,
+                    0xed => self.upper = 0x9f,
+                    _ => {}
+                }
+            }
+            0xf0..=0xf4 => {
+                self.needed = 3;
+                self.code_point = u32::from(byte & 0x07);
+                match byte {
+                    0xf0 => self.lower = 0x90,
+                    0xf4 => self.upper = 0x8f,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        None
     }
 }
+
+/// The counts come in the same order as in GNU wc
+fn display_counts(data: &FileData, flags: &WcFlags, width: usize, suffix: &str) {
+    let fields: Vec<String> = [
+        (data.num_lines, flags.lines),
+        (data.num_words, flags.words),
+        (data.num_chars, flags.chars),
+        (data.num_bytes, flags.bytes),
+        (data.max_line_length, flags.max_line_length),
+    ]
+    .iter()
+    .filter(|(_, flag)| *flag)
+    .map(|(value, _)| format!("{value:>width$}"))
+    .collect();
+    println!("{}{}", fields.join(" "), suffix);
+}
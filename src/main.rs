@@ -1,8 +1,12 @@
+use std::process::ExitCode;
 use unx::cli::interface::Cli;
 
-fn main() {
-    if let Err(e) = Cli::run() {
-        eprintln!("{e}");
-        std::process::exit(1);
+fn main() -> ExitCode {
+    match Cli::run() {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
     }
 }
@@ -0,0 +1,26 @@
+use clap::{builder::PossibleValue, ValueEnum};
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum BinaryFiles {
+    Binary,
+    Text,
+    WithoutMatch,
+}
+
+impl ValueEnum for BinaryFiles {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            BinaryFiles::Binary,
+            BinaryFiles::Text,
+            BinaryFiles::WithoutMatch,
+        ]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            BinaryFiles::Binary => PossibleValue::new("binary"),
+            BinaryFiles::Text => PossibleValue::new("text"),
+            BinaryFiles::WithoutMatch => PossibleValue::new("without-match"),
+        })
+    }
+}
@@ -0,0 +1,8 @@
+pub struct CatFlags {
+    pub number_lines: bool,
+    pub number_nonblank_lines: bool,
+    pub squeeze_blank_lines: bool,
+    pub show_nonprinting: bool,
+    pub show_ends: bool,
+    pub show_tabs: bool,
+}
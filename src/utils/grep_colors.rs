@@ -0,0 +1,98 @@
+use std::env;
+
+/// SGR sequences used for highlighting, configurable through `GREP_COLORS`
+pub struct GrepColors {
+    pub selected_match: String,
+    pub context_match: String,
+    pub selected_line: String,
+    pub context_line: String,
+    pub file_name: String,
+    pub line_number: String,
+    pub byte_offset: String,
+    pub separator: String,
+    pub reverse: bool,
+    pub no_erase: bool,
+}
+
+impl Default for GrepColors {
+    fn default() -> Self {
+        Self {
+            selected_match: "01;31".to_string(),
+            context_match: "01;31".to_string(),
+            selected_line: String::new(),
+            context_line: String::new(),
+            file_name: "35".to_string(),
+            line_number: "32".to_string(),
+            byte_offset: "32".to_string(),
+            separator: "36".to_string(),
+            reverse: false,
+            no_erase: false,
+        }
+    }
+}
+
+impl GrepColors {
+    pub fn from_env() -> Self {
+        let mut colors = Self::default();
+        if let Ok(spec) = env::var("GREP_COLORS") {
+            for capability in spec.split(':') {
+                match capability.split_once('=') {
+                    Some(("mt", sgr)) => {
+                        colors.selected_match = sgr.to_string();
+                        colors.context_match = sgr.to_string();
+                    }
+                    Some(("ms", sgr)) => colors.selected_match = sgr.to_string(),
+                    Some(("mc", sgr)) => colors.context_match = sgr.to_string(),
+                    Some(("sl", sgr)) => colors.selected_line = sgr.to_string(),
+                    Some(("cx", sgr)) => colors.context_line = sgr.to_string(),
+                    Some(("fn", sgr)) => colors.file_name = sgr.to_string(),
+                    Some(("ln", sgr)) => colors.line_number = sgr.to_string(),
+                    Some(("bn", sgr)) => colors.byte_offset = sgr.to_string(),
+                    Some(("se", sgr)) => colors.separator = sgr.to_string(),
+                    None if capability == "rv" => colors.reverse = true,
+                    None if capability == "ne" => colors.no_erase = true,
+                    _ => {} // unknown capabilities are ignored, same as in GNU grep
+                }
+            }
+        }
+        colors
+    }
+
+    pub fn start(&self, sgr: &str) -> String {
+        if sgr.is_empty() {
+            String::new()
+        } else {
+            format!("\x1b[{sgr}m{}", self.erase())
+        }
+    }
+
+    pub fn paint(&self, sgr: &str, text: &str) -> String {
+        if sgr.is_empty() {
+            text.to_string()
+        } else {
+            format!("{}{text}\x1b[m{}", self.start(sgr), self.erase())
+        }
+    }
+
+    pub fn paint_bytes(&self, sgr: &str, text: &[u8]) -> Vec<u8> {
+        if sgr.is_empty() {
+            text.to_vec()
+        } else {
+            [
+                self.start(sgr).as_bytes(),
+                text,
+                b"\x1b[m",
+                self.erase().as_bytes(),
+            ]
+            .concat()
+        }
+    }
+
+    fn erase(&self) -> &str {
+        if self.no_erase {
+            ""
+        } else {
+            "\x1b[K"
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use clap::ColorChoice;
+
+use super::binary_files::BinaryFiles;
+
+pub struct GrepFlags {
+    pub patterns: Vec<String>,
+    pub pattern_files: Vec<String>,
+    pub ignore_case: bool,
+    pub fixed_strings: bool,
+    pub perl_regexp: bool,
+    pub word_regexp: bool,
+    pub line_regexp: bool,
+    pub multiline: bool,
+    pub multiline_dotall: bool,
+    pub recursive: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub exclude_dir: Vec<String>,
+    pub respect_ignore: bool,
+    pub count: bool,
+    pub invert: bool,
+    pub after_context: Option<usize>,
+    pub before_context: Option<usize>,
+    pub context: Option<usize>,
+    pub line_number: bool,
+    pub byte_offset: bool,
+    pub with_filename: bool,
+    pub no_filename: bool,
+    pub color: ColorChoice,
+    pub only_matching: bool,
+    pub replace: Option<String>,
+    pub files_with_matches: bool,
+    pub files_without_match: bool,
+    pub quiet: bool,
+    pub json: bool,
+    pub max_count: Option<usize>,
+    pub line_buffered: bool,
+    pub binary_files: BinaryFiles,
+    pub search_zip: bool,
+}
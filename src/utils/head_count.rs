@@ -0,0 +1,74 @@
+use std::str::FromStr;
+
+/// How much of each file head prints: the first NUM lines or bytes, or with a leading '-'
+/// everything but the last NUM
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum HeadCount {
+    First(u64),
+    AllButLast(u64),
+}
+
+/// The multiplier suffix letters GNU head accepts, other than 'b', along with the power the
+/// unit is raised to; each may be followed by "B" or "D" for powers of 1000 or by "iB" for
+/// powers of 1024, the latter being the default
+const SUFFIXES: [(char, u32); 10] = [
+    ('k', 1),
+    ('K', 1),
+    ('m', 2),
+    ('M', 2),
+    ('G', 3),
+    ('T', 4),
+    ('P', 5),
+    ('E', 6),
+    ('Z', 7),
+    ('Y', 8),
+];
+
+const TOO_LARGE: &str = "Value too large for defined data type";
+
+/// What `suffix` multiplies the count by, None if it isn't a suffix at all
+fn multiplier(suffix: &str) -> Option<Result<u64, String>> {
+    if suffix == "b" {
+        return Some(Ok(512));
+    }
+    let mut chars = suffix.chars();
+    let letter = chars.next()?;
+    let (_, power) = SUFFIXES.iter().find(|(name, _)| *name == letter)?;
+    let unit: u64 = match chars.as_str() {
+        "" | "iB" => 1024,
+        "B" | "D" => 1000,
+        _ => return None,
+    };
+    Some(unit.checked_pow(*power).ok_or(TOO_LARGE.to_string()))
+}
+
+impl FromStr for HeadCount {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (all_but_last, count) = match value.strip_prefix('-') {
+            Some(count) => (true, count),
+            None => (false, value),
+        };
+        let digits_end = count
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(count.len());
+        let (digits, suffix) = count.split_at(digits_end);
+        let num = match multiplier(suffix) {
+            // like GNU head, a suffix on its own counts one unit
+            Some(multiplier) if digits.is_empty() => multiplier?,
+            Some(multiplier) => digits
+                .parse::<u64>()
+                .map_err(|e| e.to_string())?
+                .checked_mul(multiplier?)
+                .ok_or(TOO_LARGE)?,
+            // let the parser explain what's wrong with it
+            None => count.parse::<u64>().map_err(|e| e.to_string())?,
+        };
+        Ok(if all_but_last {
+            HeadCount::AllButLast(num)
+        } else {
+            HeadCount::First(num)
+        })
+    }
+}
@@ -0,0 +1,11 @@
+use super::head_count::HeadCount;
+
+#[derive(Clone, Copy)]
+pub struct HeadFlags {
+    pub lines: HeadCount,
+    pub bytes: Option<HeadCount>,
+    pub quiet: bool,
+    pub verbose: bool,
+    pub zero_terminated: bool,
+    pub total: bool,
+}
@@ -1,4 +1,13 @@
+pub mod binary_files;
+pub mod cat_flags;
 pub mod entry_type;
 pub mod extract;
+pub mod grep_colors;
+pub mod grep_flags;
+pub mod head_count;
+pub mod head_flags;
 pub mod owner;
+pub mod total_mode;
 pub mod uniq_flags;
+pub mod wc_flags;
+pub mod word_mode;
@@ -0,0 +1,30 @@
+use clap::{builder::PossibleValue, ValueEnum};
+
+/// When wc prints the line with the total counts
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum TotalMode {
+    Auto,
+    Always,
+    Only,
+    Never,
+}
+
+impl ValueEnum for TotalMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            TotalMode::Auto,
+            TotalMode::Always,
+            TotalMode::Only,
+            TotalMode::Never,
+        ]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            TotalMode::Auto => PossibleValue::new("auto"),
+            TotalMode::Always => PossibleValue::new("always"),
+            TotalMode::Only => PossibleValue::new("only"),
+            TotalMode::Never => PossibleValue::new("never"),
+        })
+    }
+}
@@ -0,0 +1,12 @@
+use super::{total_mode::TotalMode, word_mode::WordMode};
+
+#[derive(Clone, Copy)]
+pub struct WcFlags {
+    pub lines: bool,
+    pub words: bool,
+    pub bytes: bool,
+    pub chars: bool,
+    pub max_line_length: bool,
+    pub total: TotalMode,
+    pub word_mode: WordMode,
+}
@@ -0,0 +1,26 @@
+use clap::{builder::PossibleValue, ValueEnum};
+
+/// What wc takes for a word
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum WordMode {
+    /// anything between ASCII whitespace
+    Ascii,
+    /// anything between Unicode whitespace, such as no-break and ideographic spaces
+    Unicode,
+    /// words as found by the Unicode word boundaries of UAX #29
+    Uax29,
+}
+
+impl ValueEnum for WordMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[WordMode::Ascii, WordMode::Unicode, WordMode::Uax29]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            WordMode::Ascii => PossibleValue::new("ascii"),
+            WordMode::Unicode => PossibleValue::new("unicode"),
+            WordMode::Uax29 => PossibleValue::new("uax29"),
+        })
+    }
+}
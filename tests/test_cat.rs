@@ -13,6 +13,12 @@ const EMPTY: &str = "./tests/resources/cat/inputs/empty.txt";
 const FOX: &str = "./tests/resources/cat/inputs/fox.txt";
 const SPIDERS: &str = "./tests/resources/cat/inputs/spiders.txt";
 const BUSTLE: &str = "./tests/resources/cat/inputs/the-bustle.txt";
+const CONTROL: &str = "./tests/resources/cat/inputs/control.txt";
+const BINARY: &str = "./tests/resources/cat/inputs/binary.bin";
+const BLANKS: &str = "./tests/resources/cat/inputs/blanks.txt";
+const UNTERMINATED: &str = "./tests/resources/cat/inputs/unterminated.txt";
+const CARRIAGE_RETURN: &str = "./tests/resources/cat/inputs/carriage-return.txt";
+const INPUTS_DIR: &str = "./tests/resources/cat/inputs";
 
 // --------------------------------------------------
 #[test]
@@ -39,17 +45,44 @@ fn run(args: &[&str]) -> Result<()> {
     helpers::run(PRG, SUBCMD, args)
 }
 
+// --------------------------------------------------
+/// Compares raw output for every combination of flags and inputs, with BLANKS fed to "-"
+fn run_matrix(flag_sets: &[&[&str]], input_sets: &[&[&str]]) -> Result<()> {
+    let stdin = std::fs::read(BLANKS)?;
+    for flags in flag_sets {
+        for inputs in input_sets {
+            let args = [*flags, *inputs].concat();
+            let expected = std::process::Command::new(SUBCMD)
+                .args(&args)
+                .stdin(std::fs::File::open(BLANKS)?)
+                .output()?;
+            let actual = Command::cargo_bin(PRG)?
+                .arg(SUBCMD)
+                .args(&args)
+                .write_stdin(stdin.clone())
+                .output()?;
+            assert_eq!(
+                String::from_utf8_lossy(&expected.stdout),
+                String::from_utf8_lossy(&actual.stdout),
+                "cat {}",
+                args.join(" ")
+            );
+        }
+    }
+    Ok(())
+}
+
 // --------------------------------------------------
 fn run_stdin(input_file: &str, args: &[&str]) -> Result<()> {
     let expected = std::process::Command::new(SUBCMD)
-        .arg(&input_file)
+        .arg(input_file)
         .args(args)
         .output()
         .unwrap();
 
     let actual = Command::cargo_bin(PRG)?
         .arg(SUBCMD)
-        .arg(&input_file)
+        .arg(input_file)
         .args(args)
         .output()
         .unwrap();
@@ -167,3 +200,137 @@ fn all_n() -> Result<()> {
 fn all_b() -> Result<()> {
     run(&["-b", FOX, SPIDERS, BUSTLE])
 }
+
+// --------------------------------------------------
+#[test]
+fn control_raw() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &[CONTROL])
+}
+
+// --------------------------------------------------
+#[test]
+fn control_v() -> Result<()> {
+    run(&["-v", CONTROL])
+}
+
+// --------------------------------------------------
+#[test]
+fn control_show_nonprinting_n() -> Result<()> {
+    run(&["--show-nonprinting", "-n", CONTROL])
+}
+
+// --------------------------------------------------
+#[test]
+fn control_e() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &["-E", CONTROL])
+}
+
+// --------------------------------------------------
+#[test]
+fn control_t() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &["--show-tabs", CONTROL])
+}
+
+// --------------------------------------------------
+#[test]
+fn control_a() -> Result<()> {
+    run(&["-A", CONTROL])
+}
+
+// --------------------------------------------------
+#[test]
+fn control_show_all_squeeze() -> Result<()> {
+    run(&["--show-all", "-s", CONTROL])
+}
+
+// --------------------------------------------------
+#[test]
+fn control_e_short() -> Result<()> {
+    run(&["-e", CONTROL])
+}
+
+// --------------------------------------------------
+#[test]
+fn control_t_short() -> Result<()> {
+    run(&["-t", CONTROL])
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_b_show_ends() -> Result<()> {
+    run(&["-b", "--show-ends", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn spiders_show_tabs_n() -> Result<()> {
+    run(&["-T", "-n", SPIDERS])
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_raw() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &[BINARY])
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_between_files() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &[FOX, BINARY, CONTROL])
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_n() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &["-n", BINARY])
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_b_squeeze() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &["-bs", BINARY])
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_stdin() -> Result<()> {
+    let input = std::fs::read(BINARY)?;
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .write_stdin(input.clone())
+        .assert()
+        .success()
+        .stdout(input);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn across_files() -> Result<()> {
+    run_matrix(
+        &[
+            &[],
+            &["-n"],
+            &["-b"],
+            &["-s"],
+            &["-ns"],
+            &["-bs"],
+            &["-E"],
+            &["-nE"],
+            &["-bsA"],
+        ],
+        &[
+            &[BLANKS, BLANKS],
+            &[UNTERMINATED, BLANKS],
+            &[UNTERMINATED, UNTERMINATED, FOX],
+            &[BLANKS, "-", UNTERMINATED],
+            &[UNTERMINATED, "-", BLANKS],
+            &[CARRIAGE_RETURN, BLANKS],
+            &[CARRIAGE_RETURN, FOX, CARRIAGE_RETURN],
+            &[EMPTY, BLANKS, EMPTY, BLANKS],
+            &["-", SPIDERS, "-"],
+            &[UNTERMINATED, INPUTS_DIR, BLANKS],
+            &[BLANKS, INPUTS_DIR, FOX],
+        ],
+    )
+}
@@ -45,7 +45,7 @@ fn dies_bad_type() -> Result<()> {
 }
 
 // --------------------------------------------------
-fn format_file_name(expected_file: &str) -> Cow<str> {
+fn format_file_name(expected_file: &str) -> Cow<'_, str> {
     expected_file.into()
 }
 
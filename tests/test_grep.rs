@@ -4,6 +4,7 @@ use anyhow::Result;
 use assert_cmd::Command;
 use predicates::prelude::*;
 use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
 use std::fs;
 
 use utils::helpers;
@@ -15,6 +16,20 @@ const EMPTY: &str = "./tests/resources/grep/inputs/empty.txt";
 const FOX: &str = "./tests/resources/grep/inputs/fox.txt";
 const NOBODY: &str = "./tests/resources/grep/inputs/nobody.txt";
 const INPUTS_DIR: &str = "./tests/resources/grep/inputs";
+const IGNORED_DIR: &str = "./tests/resources/grep/ignored";
+const RESOURCES_DIR: &str = "./tests/resources/grep";
+const BINARY: &str = "./tests/resources/grep/binary/fox.bin";
+const LATIN1: &str = "./tests/resources/grep/binary/latin1.txt";
+const REPEATS: &str = "./tests/resources/grep/perl/repeats.txt";
+#[cfg(feature = "perl-regexp")]
+const BACKTRACKING: &str = "./tests/resources/grep/perl/backtracking.txt";
+const TRACE: &str = "./tests/resources/grep/multiline/trace.txt";
+const COMPRESSED_DIR: &str = "./tests/resources/grep/compressed";
+const GZIP: &str = "./tests/resources/grep/compressed/bustle.txt.gz";
+const BZIP2: &str = "./tests/resources/grep/compressed/nobody.txt.bz2";
+const XZ: &str = "./tests/resources/grep/compressed/fox.log";
+const THE_YOU: &str = "./tests/resources/grep/patterns/the_you.txt";
+const WORDS: &str = "./tests/resources/grep/patterns/words.txt";
 
 // --------------------------------------------------
 #[test]
@@ -29,7 +44,7 @@ fn dies_bad_pattern() -> Result<()> {
         .arg(SUBCMD)
         .args(["*foo", FOX])
         .assert()
-        .failure()
+        .code(2)
         .stderr(predicate::str::contains(r#"grep: invalid pattern `*foo`"#));
     Ok(())
 }
@@ -40,7 +55,13 @@ fn warns_bad_file() -> Result<()> {
     let bad = helpers::generate_bad_file();
     let args = ["foo", &bad];
     let expected = format!("{SUBCMD}: {bad}: .* [(]os error 2[)]");
-    helpers::skips_bad_entry(PRG, SUBCMD, &args, &expected)
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(args)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
 }
 
 // --------------------------------------------------
@@ -48,6 +69,23 @@ fn run(args: &[&str]) -> Result<()> {
     helpers::run(PRG, SUBCMD, args)
 }
 
+/// Compares a search of `compressed` with `--search-zip` against system grep on `plain`
+fn run_compressed(args: &[&str], compressed: &str, plain: &str) -> Result<()> {
+    let expected = std::process::Command::new(SUBCMD)
+        .args(args)
+        .arg(plain)
+        .output()?;
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .arg("--search-zip")
+        .args(args)
+        .arg(compressed)
+        .assert()
+        .code(expected.status.code().unwrap())
+        .stdout(String::from_utf8(expected.stdout)?);
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn empty_file() -> Result<()> {
@@ -114,6 +152,29 @@ fn recursive_ignore_case() -> Result<()> {
     run(&["-ri", "then", INPUTS_DIR])
 }
 
+// --------------------------------------------------
+#[test]
+fn recursive_without_file() -> Result<()> {
+    // the working directory is searched, not stdin, whose input is never read
+    let expected = std::process::Command::new(SUBCMD)
+        .args(["-rn", "the"])
+        .current_dir(INPUTS_DIR)
+        .stdin(std::process::Stdio::null())
+        .output()?;
+    let actual = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-rn", "the"])
+        .current_dir(INPUTS_DIR)
+        .write_stdin("the standard input\n")
+        .output()?;
+    assert!(actual.status.success());
+    assert_eq!(
+        String::from_utf8(expected.stdout)?,
+        String::from_utf8(actual.stdout)?
+    );
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn sensitive_count_capital() -> Result<()> {
@@ -158,61 +219,1080 @@ fn insensitive_count_multiple() -> Result<()> {
 
 // --------------------------------------------------
 #[test]
-fn warns_dir_not_recursive() -> Result<()> {
-    let stdout = "The quick brown fox jumps over the lazy dog.";
+fn after_context() -> Result<()> {
+    run(&["-A", "1", "The", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn before_context() -> Result<()> {
+    run(&["--before-context", "2", "the", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn context() -> Result<()> {
+    run(&["-C", "1", "Nobody", NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn context_zero() -> Result<()> {
+    run(&["-C", "0", "The", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn context_overridden() -> Result<()> {
+    run(&["-A", "0", "-C", "2", "The", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn context_invert() -> Result<()> {
+    run(&["-v", "-B", "1", "The", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn context_multiple_files() -> Result<()> {
+    run(&["-A", "1", "The", BUSTLE, EMPTY, NOBODY, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn context_count() -> Result<()> {
+    run(&["-c", "-C", "1", "The", BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn line_number() -> Result<()> {
+    run(&["-n", "the", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn byte_offset() -> Result<()> {
+    run(&["--byte-offset", "-i", "nobody", NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn line_number_byte_offset_context() -> Result<()> {
+    run(&["-nb", "-C", "1", "The", BUSTLE, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn with_filename() -> Result<()> {
+    run(&["-H", "-n", "The", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn no_filename() -> Result<()> {
+    run(&["--no-filename", "The", BUSTLE, EMPTY, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn filename_last_flag_wins() -> Result<()> {
+    run(&["-H", "-h", "The", BUSTLE, FOX])?;
+    run(&["-h", "-H", "The", FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn with_filename_count() -> Result<()> {
+    run(&["-Hc", "The", FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn no_filename_count() -> Result<()> {
+    run(&["-hc", "The", BUSTLE, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn color_always() -> Result<()> {
+    run(&["--color=always", "-n", "the", BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn color_always_context() -> Result<()> {
+    run(&["--color=always", "-b", "-C", "1", "The", BUSTLE, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn color_always_invert() -> Result<()> {
+    run(&["--colour=always", "-v", "-A", "1", "house", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn color_always_count() -> Result<()> {
+    run(&["--color=always", "-c", "The", BUSTLE, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn color_auto_not_terminal() -> Result<()> {
     Command::cargo_bin(PRG)?
         .arg(SUBCMD)
-        .args(["fox", INPUTS_DIR, FOX])
+        .args(["--color", "fox", FOX])
         .assert()
-        .stderr(predicate::str::contains(
-            "./tests/resources/grep/inputs is a directory",
-        ))
-        .stdout(predicate::str::contains(stdout));
+        .success()
+        .stdout("The quick brown fox jumps over the lazy dog.\n");
     Ok(())
 }
 
 // --------------------------------------------------
 #[test]
-fn stdin() -> Result<()> {
-    let input = fs::read_to_string(BUSTLE)?;
-    let expected =
-        fs::read_to_string("./tests/resources/grep/expected/bustle.txt.the.capitalized")?;
+fn grep_colors_env() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--color=always", "-n", "fox", FOX])
+        .env("GREP_COLORS", "ms=4:ln=1:se=:sl=2:ne")
+        .assert()
+        .success()
+        .stdout(
+            "\x1b[1m1\x1b[m:\x1b[2mThe quick brown \x1b[4mfox\x1b[m\
+             \x1b[2m jumps over the lazy dog.\x1b[m\n",
+        );
+    Ok(())
+}
 
-    let output = Command::cargo_bin(PRG)?
+// --------------------------------------------------
+#[test]
+fn only_matching() -> Result<()> {
+    run(&["-o", "-i", "nobody", NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching_context() -> Result<()> {
+    run(&["-onb", "-C", "1", "[Tt]he", BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching_invert() -> Result<()> {
+    run(&["-o", "-v", "The", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching_empty_matches() -> Result<()> {
+    run(&["--only-matching", "x*", FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching_color() -> Result<()> {
+    run(&["-o", "--color=always", "-H", "the", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn files_with_matches() -> Result<()> {
+    run(&["-l", "The", BUSTLE, EMPTY, FOX, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn files_with_matches_invert() -> Result<()> {
+    run(&["--files-with-matches", "-v", "The", FOX, BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn files_without_match() -> Result<()> {
+    run(&["-L", "The", BUSTLE, EMPTY, FOX, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn files_without_match_none_selected() -> Result<()> {
+    run(&["--files-without-match", "The", EMPTY])
+}
+
+// --------------------------------------------------
+#[test]
+fn files_with_matches_overrides_count() -> Result<()> {
+    run(&["-c", "-l", "the", BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn quiet() -> Result<()> {
+    run(&["-q", "The", BUSTLE, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn quiet_no_match() -> Result<()> {
+    run(&["--quiet", "foo", BUSTLE, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn exit_status_no_match() -> Result<()> {
+    Command::cargo_bin(PRG)?
         .arg(SUBCMD)
-        .arg("The")
-        .write_stdin(input)
-        .output()
-        .expect("fail");
-    assert!(output.status.success());
+        .args(["foo", BUSTLE, FOX])
+        .assert()
+        .code(1)
+        .stdout("");
+    Ok(())
+}
 
-    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
-    assert_eq!(stdout, expected);
+// --------------------------------------------------
+#[test]
+fn exit_status_match_and_bad_file() -> Result<()> {
+    let bad = helpers::generate_bad_file();
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["fox", &bad, FOX])
+        .assert()
+        .code(2)
+        .stdout(format!(
+            "{FOX}:The quick brown fox jumps over the lazy dog.\n"
+        ));
     Ok(())
 }
 
 // --------------------------------------------------
 #[test]
-fn stdin_insensitive_count() -> Result<()> {
-    let files = &[BUSTLE, EMPTY, FOX, NOBODY];
+fn quiet_match_after_bad_file() -> Result<()> {
+    let bad = helpers::generate_bad_file();
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-q", "The", &bad, FOX])
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
 
-    let mut input = String::new();
-    for file in files {
-        input += &fs::read_to_string(file)?;
-    }
+// --------------------------------------------------
+#[test]
+fn multiple_patterns() -> Result<()> {
+    run(&["-e", "The", "-e", "you", BUSTLE, NOBODY])
+}
 
-    let expected_file = "./tests/resources/grep/expected/the.recursive.insensitive.count.stdin";
-    let expected = fs::read_to_string(expected_file)?;
+// --------------------------------------------------
+#[test]
+fn pattern_option_first_positional_is_file() -> Result<()> {
+    run(&["--regexp", "house", FOX, BUSTLE])
+}
 
-    let output = Command::cargo_bin(PRG)?
+// --------------------------------------------------
+#[test]
+fn multiline_pattern() -> Result<()> {
+    run(&["-e", "fox\nhouse", BUSTLE, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_file() -> Result<()> {
+    run(&["-f", THE_YOU, BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_file_and_patterns() -> Result<()> {
+    run(&["-n", "--file", WORDS, "-e", "fox", BUSTLE, NOBODY, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_file_invert_count() -> Result<()> {
+    run(&["-vc", "-f", THE_YOU, BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_file_empty() -> Result<()> {
+    run(&["-f", EMPTY, BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_file_stdin() -> Result<()> {
+    Command::cargo_bin(PRG)?
         .arg(SUBCMD)
-        .args(["-ci", "the", "-"])
-        .write_stdin(input)
-        .output()
-        .expect("fail");
-    assert!(output.status.success());
+        .args(["-f", "-", FOX, EMPTY])
+        .write_stdin("dog\nfox\n")
+        .assert()
+        .success()
+        .stdout(format!(
+            "{FOX}:The quick brown fox jumps over the lazy dog.\n"
+        ));
+    Ok(())
+}
 
-    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
-    assert_eq!(stdout, expected);
+// --------------------------------------------------
+#[test]
+fn dies_bad_pattern_file() -> Result<()> {
+    let bad = helpers::generate_bad_file();
+    let expected = format!("{SUBCMD}: {bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-f", &bad, FOX])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_pattern_among_many() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-e", "fox", "-e", "a)(b", FOX])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("grep: invalid pattern `a)(b`"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fixed_strings() -> Result<()> {
+    run(&["-F", "a.h", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn fixed_strings_special_chars() -> Result<()> {
+    run(&["--fixed-strings", "-e", "(", "-e", "*", "-e", "!", NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn fixed_strings_ignore_case() -> Result<()> {
+    run(&["-Fi", "THE", BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn fixed_strings_only_matching_longest() -> Result<()> {
+    run(&["-Fo", "-e", "th", "-e", "the", "-e", "he", NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn fixed_strings_pattern_file() -> Result<()> {
+    run(&["-F", "-f", THE_YOU, "--color=always", BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp() -> Result<()> {
+    run(&["-w", "the", BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp_non_word_edges() -> Result<()> {
+    run(&["--word-regexp", "Nobody!", NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp_only_matching_ignore_case() -> Result<()> {
+    run(&["-woi", "the", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp_fixed_strings() -> Result<()> {
+    run(&["-Fwi", "nobody", NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn line_regexp() -> Result<()> {
+    run(&["-x", "The.bustle.in.a.house", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn line_regexp_fixed_strings() -> Result<()> {
+    run(&["--line-regexp", "-F", "The.bustle.in.a.house", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn line_regexp_ignore_case_count() -> Result<()> {
+    run(&["-xic", "the BUSTLE in a house", BUSTLE, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count_trailing_context() -> Result<()> {
+    run(&["-m", "1", "-A", "2", "The", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count_line_number() -> Result<()> {
+    run(&["-m2", "-A1", "-n", "the", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count_multiple_files() -> Result<()> {
+    run(&["-m", "2", "-A5", "-n", "Th", BUSTLE, NOBODY])
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count_count() -> Result<()> {
+    run(&["-m1", "-c", "the", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count_zero() -> Result<()> {
+    run(&["--max-count=0", "the", BUSTLE])
+}
+
+// --------------------------------------------------
+#[test]
+fn line_buffered() -> Result<()> {
+    run(&["--line-buffered", "-n", "the", BUSTLE, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_output_order() -> Result<()> {
+    run(&[
+        "-rn",
+        "-C1",
+        "--binary-files=without-match",
+        "the",
+        RESOURCES_DIR,
+    ])
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_count_all_files() -> Result<()> {
+    run(&["-rc", "fox", RESOURCES_DIR])
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_include() -> Result<()> {
+    run(&[
+        "-r",
+        "--include=*.txt",
+        "--include=*.count",
+        "the",
+        RESOURCES_DIR,
+    ])
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_exclude() -> Result<()> {
+    run(&["-ri", "--exclude=*.txt", "then", RESOURCES_DIR])
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_exclude_dir() -> Result<()> {
+    run(&["-rl", "--exclude-dir=expected", "fox", RESOURCES_DIR])
+}
+
+// --------------------------------------------------
+#[test]
+fn exclude_dir_command_line() -> Result<()> {
+    run(&["-r", "--exclude-dir=inputs", "fox", INPUTS_DIR, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn exclude_file_command_line() -> Result<()> {
+    run(&["--exclude=*/bustle.txt", "the", BUSTLE, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_quiet() -> Result<()> {
+    run(&["-rq", "fox", RESOURCES_DIR])
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_ignore_files_by_default() -> Result<()> {
+    run(&["-r", "fox", IGNORED_DIR])
+}
+
+// --------------------------------------------------
+#[test]
+fn respect_ignore() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-r", "--respect-ignore", "fox", IGNORED_DIR])
+        .assert()
+        .success()
+        .stdout("./tests/resources/grep/ignored/kept.txt:a fox that is kept\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_file_matches() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-n", "fox", BINARY, FOX])
+        .assert()
+        .success()
+        .stdout(concat!(
+            "Binary file ./tests/resources/grep/binary/fox.bin matches\n",
+            "./tests/resources/grep/inputs/fox.txt:1:The quick brown fox jumps over the lazy dog.\n"
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_file_count() -> Result<()> {
+    run(&["-c", "fox", BINARY])
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_file_list() -> Result<()> {
+    run(&["-l", "fox", BINARY, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_files_without_match() -> Result<()> {
+    run(&["--binary-files=without-match", "fox", BINARY, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_files_text() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--binary-files=text", "-n", "fox", BINARY])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        b"1:a fox\0 in a binary file\n2:one more fox\n".to_vec()
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn text_short() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-ao", "[a-z]* fox", BINARY])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"a fox\nmore fox\n".to_vec());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn text_then_binary_files() -> Result<()> {
+    // the last of -a and --binary-files wins, like GNU grep
+    run(&["-a", "--binary-files=without-match", "fox", BINARY, FOX])
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_files_then_text() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--binary-files=without-match", "-a", "-n", "fox", BINARY])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        b"1:a fox\0 in a binary file\n2:one more fox\n".to_vec()
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn invalid_utf8() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-b", "fox", LATIN1])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"0:caf\xe9 au lait for a fox\n".to_vec());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_lookaround_without_perl() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["(?<=foo )foo", REPEATS])
+        .assert()
+        .code(2)
+        .stderr("grep: invalid pattern `(?<=foo )foo`: look-around assertions require -P\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_backreference_without_perl() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args([r"(\w+) \1", REPEATS])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("backreferences require -P"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(not(feature = "perl-regexp"))]
+#[test]
+fn dies_perl_regexp_disabled() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-P", "foo", REPEATS])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("`perl-regexp` feature"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(feature = "perl-regexp")]
+#[test]
+fn perl_regexp_lookbehind() -> Result<()> {
+    run(&["-P", r"(?<=foo\s)foo", REPEATS])
+}
+
+// --------------------------------------------------
+#[cfg(feature = "perl-regexp")]
+#[test]
+fn perl_regexp_lookahead_only_matching() -> Result<()> {
+    run(&["-Pon", "(?<!foo)bar(?=bar|$)", REPEATS])
+}
+
+// --------------------------------------------------
+#[cfg(feature = "perl-regexp")]
+#[test]
+fn perl_regexp_backreference() -> Result<()> {
+    run(&["--perl-regexp", "--color=always", r"(\w+)\s?\1", REPEATS])
+}
+
+// --------------------------------------------------
+#[cfg(feature = "perl-regexp")]
+#[test]
+fn perl_regexp_word_line_ignore_case() -> Result<()> {
+    run(&["-Pwic", "BAR", REPEATS])?;
+    run(&["-Pxi", r"(B\w)r\1r", REPEATS])
+}
+
+// --------------------------------------------------
+#[cfg(feature = "perl-regexp")]
+#[test]
+fn dies_perl_regexp_backtracking_limit() -> Result<()> {
+    // the backreference keeps the whole pattern on the backtracking engine, which gives up
+    // on the second line; the search of the file stops there with an error, as in GNU grep
+    for (args, stdout) in [
+        (&[][..], "aaaa\n"),
+        (&["-v"][..], ""),
+        (&["-o"][..], "aaaa\n"),
+        (&["-c"][..], ""),
+    ] {
+        Command::cargo_bin(PRG)?
+            .arg(SUBCMD)
+            .args(args)
+            .args(["-P", r"(a|aa)+\1$", BACKTRACKING])
+            .assert()
+            .code(2)
+            .stdout(stdout)
+            .stderr(predicate::str::contains(format!("grep: {BACKTRACKING}: ")));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(feature = "perl-regexp")]
+#[test]
+fn dies_perl_regexp_multiple_patterns() -> Result<()> {
+    run(&["-P", "-e", "foo", "-e", "bar", REPEATS])
+}
+
+// --------------------------------------------------
+#[test]
+fn multiline() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-Un", r"Error:.*\n\s+at", TRACE])
+        .assert()
+        .success()
+        .stdout("2:Error: boom\n3:    at foo (a.js:1)\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiline_context() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--multiline", "-C1", "-n", r"quiet\nlast", TRACE])
+        .assert()
+        .success()
+        .stdout("5-ok again\n6:Error: quiet\n7:last\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiline_dotall_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-U", "--multiline-dotall", "-c", "Error.*bar", TRACE])
+        .assert()
+        .success()
+        .stdout("3\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiline_only_matching_byte_offset() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-Uob", r"boom\n\s+at", TRACE])
+        .assert()
+        .success()
+        .stdout("15:boom\n20:    at\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiline_color() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-U", "--color=always", r"boom\n\s+at foo", TRACE])
+        .assert()
+        .success()
+        .stdout(concat!(
+            "Error: \x1b[01;31m\x1b[Kboom\x1b[m\x1b[K\n",
+            "\x1b[01;31m\x1b[K    at foo\x1b[m\x1b[K (a.js:1)\n"
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiline_single_lines() -> Result<()> {
+    run(&["-U", "-n", "-x", "ok.*", TRACE])
+}
+
+// --------------------------------------------------
+#[test]
+fn search_zip_gzip() -> Result<()> {
+    run_compressed(&["-n", "the"], GZIP, BUSTLE)
+}
+
+// --------------------------------------------------
+#[test]
+fn search_zip_bzip2() -> Result<()> {
+    run_compressed(&["-ci", "nobody"], BZIP2, NOBODY)
+}
+
+// --------------------------------------------------
+#[test]
+fn search_zip_xz_by_magic_bytes() -> Result<()> {
+    run_compressed(&["-b", "-o", "fox"], XZ, FOX)
+}
+
+// --------------------------------------------------
+#[test]
+fn search_zip_recursive() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-rzl", "The", COMPRESSED_DIR, FOX])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("compressed/concatenated.gz\n"))
+        .stdout(predicate::str::contains("compressed/bustle.txt.gz\n"))
+        .stdout(predicate::str::contains("compressed/fox.log\n"))
+        .stdout(predicate::str::contains("inputs/fox.txt\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn search_zip_plain_file() -> Result<()> {
+    run_compressed(&["-n", "-A1", "the"], BUSTLE, BUSTLE)
+}
+
+// --------------------------------------------------
+#[test]
+fn without_search_zip_compressed_is_binary() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-c", "fox", GZIP])
+        .assert()
+        .code(1)
+        .stdout("0\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn json() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--json", "-A1", "fox|the", FOX, BUSTLE, EMPTY])
+        .output()?;
+    assert!(output.status.success());
+
+    let events: Vec<Value> = String::from_utf8(output.stdout)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    let types: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+    assert_eq!(
+        types,
+        ["begin", "match", "end", "begin", "match", "context", "end", "summary"]
+    );
+
+    assert_eq!(events[0]["data"]["path"]["text"], FOX);
+    let data = &events[1]["data"];
+    assert_eq!(
+        data["lines"]["text"],
+        "The quick brown fox jumps over the lazy dog.\n"
+    );
+    assert_eq!(data["line_number"], 1);
+    assert_eq!(data["absolute_offset"], 0);
+    assert_eq!(
+        data["submatches"],
+        json!([
+            { "match": { "text": "fox" }, "start": 16, "end": 19 },
+            { "match": { "text": "the" }, "start": 31, "end": 34 },
+        ])
+    );
+
+    let data = &events[5]["data"];
+    assert_eq!(data["line_number"], 7);
+    assert_eq!(data["absolute_offset"], 124);
+    assert_eq!(data["submatches"], json!([]));
+
+    let stats = &events[6]["data"]["stats"];
+    assert_eq!(stats["matched_lines"], 1);
+    assert_eq!(stats["matches"], 1);
+
+    let stats = &events[7]["data"]["stats"];
+    assert_eq!(stats["searches"], 3);
+    assert_eq!(stats["searches_with_match"], 2);
+    assert_eq!(stats["matched_lines"], 2);
+    assert_eq!(stats["matches"], 3);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn json_invalid_utf8() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--json", "fox", LATIN1])
+        .output()?;
+    let events: Vec<Value> = String::from_utf8(output.stdout)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(
+        events[1]["data"]["lines"],
+        json!({ "bytes": "Y2Fm6SBhdSBsYWl0IGZvciBhIGZveAo=" })
+    );
+    assert_eq!(
+        events[1]["data"]["submatches"][0]["match"],
+        json!({ "text": "fox" })
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn json_no_match() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--json", "nothing", FOX])
+        .output()?;
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8(output.stdout)?;
+    let summary: Value = serde_json::from_str(stdout.trim_end())?;
+    assert_eq!(summary["type"], "summary");
+    assert_eq!(summary["data"]["stats"]["searches"], 1);
+    assert_eq!(summary["data"]["stats"]["searches_with_match"], 0);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_json_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--json", "-c", "fox", FOX])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn replace() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--replace", "[$1]", r"(qu\w+) brown", FOX])
+        .assert()
+        .success()
+        .stdout("The [quick] fox jumps over the lazy dog.\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn replace_named_groups_only_matching() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-on", "--replace", "${animal}!", "(?P<animal>fox|dog)", FOX])
+        .assert()
+        .success()
+        .stdout("1:fox!\n1:dog!\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn replace_escapes_and_unknown_groups() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--replace", "$$1=$1 ${2}x $nope", "(b)(r)", FOX])
+        .assert()
+        .success()
+        .stdout("The quick $1=b rx own fox jumps over the lazy dog.\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn replace_fixed_strings_keeps_context() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-F", "-n", "-B1", "--replace", "$0$0", "the", BUSTLE])
+        .assert()
+        .success()
+        .stdout("5-\n6:The sweeping up thethe heart,\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn replace_color() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--color=always", "--replace", "THE", "the", BUSTLE])
+        .assert()
+        .success()
+        .stdout("The sweeping up \x1b[01;31m\x1b[KTHE\x1b[m\x1b[K heart,\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn warns_dir_not_recursive() -> Result<()> {
+    let stdout = "The quick brown fox jumps over the lazy dog.";
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["fox", INPUTS_DIR, FOX])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "./tests/resources/grep/inputs is a directory",
+        ))
+        .stdout(predicate::str::contains(stdout));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin() -> Result<()> {
+    let input = fs::read_to_string(BUSTLE)?;
+    let expected =
+        fs::read_to_string("./tests/resources/grep/expected/bustle.txt.the.capitalized")?;
+
+    let output = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .arg("The")
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_insensitive_count() -> Result<()> {
+    let files = &[BUSTLE, EMPTY, FOX, NOBODY];
+
+    let mut input = String::new();
+    for file in files {
+        input += &fs::read_to_string(file)?;
+    }
+
+    let expected_file = "./tests/resources/grep/expected/the.recursive.insensitive.count.stdin";
+    let expected = fs::read_to_string(expected_file)?;
+
+    let output = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-ci", "the", "-"])
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_with_filename() -> Result<()> {
+    let input = fs::read_to_string(FOX)?;
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-Hn", "fox"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("(standard input):1:The quick brown fox jumps over the lazy dog.\n");
     Ok(())
 }
@@ -17,6 +17,7 @@ const ONE: &str = "./tests/resources/head/inputs/one.txt";
 const TWO: &str = "./tests/resources/head/inputs/two.txt";
 const THREE: &str = "./tests/resources/head/inputs/three.txt";
 const TWELVE: &str = "./tests/resources/head/inputs/twelve.txt";
+const RECORDS: &str = "./tests/resources/head/inputs/records.bin";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -114,6 +115,26 @@ fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> Result<()>
     Ok(())
 }
 
+// --------------------------------------------------
+/// Feeds the same input to the system head through stdin
+fn run_stdin_like_system(args: &[&str], input_file: &str) -> Result<()> {
+    let expected = std::process::Command::new(SUBCMD)
+        .args(args)
+        .stdin(File::open(input_file)?)
+        .output()?;
+    let actual = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(args)
+        .write_stdin(fs::read(input_file)?)
+        .output()?;
+    assert!(actual.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&expected.stdout),
+        String::from_utf8_lossy(&actual.stdout)
+    );
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn empty() -> Result<()> {
@@ -411,3 +432,189 @@ fn multiple_files_c2() -> Result<()> {
 fn multiple_files_c4() -> Result<()> {
     run(&["-c", "4", EMPTY, ONE, TWO, THREE, TWELVE])
 }
+
+// --------------------------------------------------
+#[test]
+fn all_but_last_lines() -> Result<()> {
+    run(&["-n", "-2", TWELVE])
+}
+
+#[test]
+fn all_but_last_zero_lines() -> Result<()> {
+    run(&["-n", "-0", TWELVE])
+}
+
+#[test]
+fn all_but_more_lines_than_there_are() -> Result<()> {
+    run(&["-n", "-20", THREE])
+}
+
+#[test]
+fn all_but_last_bytes() -> Result<()> {
+    run(&["-c", "-5", TWELVE])
+}
+
+#[test]
+fn all_but_last_bytes_attached() -> Result<()> {
+    run(&["-c-5", TWO, TWELVE])
+}
+
+#[test]
+fn all_but_last_lines_multiple_files() -> Result<()> {
+    run(&["--lines=-1", EMPTY, ONE, TWO, THREE, TWELVE])
+}
+
+#[test]
+fn all_but_last_lines_stdin() -> Result<()> {
+    run_stdin_like_system(&["-n", "-3"], TWELVE)
+}
+
+#[test]
+fn all_but_last_bytes_stdin() -> Result<()> {
+    run_stdin_like_system(&["-c", "-10"], TWELVE)
+}
+
+// --------------------------------------------------
+#[test]
+fn bytes_suffix() -> Result<()> {
+    run(&["-c", "1K", TWELVE])
+}
+
+#[test]
+fn lines_suffix() -> Result<()> {
+    run(&["-n", "1KiB", TWELVE])
+}
+
+#[test]
+fn lowercase_and_decimal_suffixes() -> Result<()> {
+    for count in ["1k", "1m", "1KB", "1kiB", "1kD", "K"] {
+        run(&["-c", count, TWELVE])?;
+    }
+    Ok(())
+}
+
+#[test]
+fn all_but_last_suffix() -> Result<()> {
+    run(&["-c", "-1b", TWELVE])
+}
+
+#[test]
+fn dies_too_large() -> Result<()> {
+    for count in ["99999999E", "1Z", "1Y", "1ZB"] {
+        Command::cargo_bin(PRG)?
+            .arg(SUBCMD)
+            .args(["-c", count, TWELVE])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Value too large for defined data type",
+            ));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_bytes() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &["-c", "40", RECORDS])
+}
+
+#[test]
+fn binary_lines() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &["-n", "3", RECORDS])
+}
+
+#[test]
+fn bytes_larger_than_a_read() -> Result<()> {
+    let input = "x".repeat(200_000);
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-c", "150000"])
+        .write_stdin(input.clone())
+        .assert()
+        .success()
+        .stdout(input[..150_000].to_string());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn zero_terminated() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &["-z", RECORDS])
+}
+
+#[test]
+fn zero_terminated_n2() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &["--zero-terminated", "-n", "2", RECORDS])
+}
+
+#[test]
+fn zero_terminated_all_but_last() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &["-z", "-n", "-3", RECORDS, TWO])
+}
+
+#[test]
+fn zero_terminated_bytes() -> Result<()> {
+    helpers::run_bytes(PRG, SUBCMD, &["-z", "-c", "10", RECORDS])
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_header() -> Result<()> {
+    run_stdin_like_system(&["-n", "2", "-", ONE], TWELVE)
+}
+
+#[test]
+fn stdin_header_verbose() -> Result<()> {
+    run_stdin_like_system(&["-v"], THREE)
+}
+
+// --------------------------------------------------
+#[test]
+fn total_lines() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--total", "-n", "4", "-q", ONE, TWO, TWELVE])
+        .assert()
+        .success()
+        .stdout("Öne line, four words.\nTwo lines.\nFour words.\none\n");
+    Ok(())
+}
+
+#[test]
+fn total_lines_stops_at_budget() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--total", "-n", "3", TWO, ONE, TWELVE])
+        .assert()
+        .success()
+        .stdout(
+            "==> ./tests/resources/head/inputs/two.txt <==\nTwo lines.\nFour words.\n\n\
+             ==> ./tests/resources/head/inputs/one.txt <==\nÖne line, four words.\n",
+        );
+    Ok(())
+}
+
+#[test]
+fn total_bytes() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--total", "-c", "16", "-q", TWO, TWELVE])
+        .assert()
+        .success()
+        .stdout("Two lines.\nFour ");
+    Ok(())
+}
+
+#[test]
+fn dies_total_all_but_last() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--total", "-n", "-2", TWELVE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--total can't be combined with a negative count",
+        ));
+    Ok(())
+}
@@ -1,10 +1,12 @@
 pub mod utils;
 
 use anyhow::Result;
-use assert_cmd::Command;
+use assert_cmd::{cargo::CommandCargoExt, Command};
 use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use std::fs;
+use std::io::Write;
+use tempfile::NamedTempFile;
 
 use utils::helpers;
 
@@ -13,6 +15,11 @@ const SUBCMD: &str = "wc";
 const EMPTY: &str = "./tests/resources/wc/inputs/empty.txt";
 const FOX: &str = "./tests/resources/wc/inputs/fox.txt";
 const ATLAMAL: &str = "./tests/resources/wc/inputs/atlamal.txt";
+const WIDE: &str = "./tests/resources/wc/inputs/wide.txt";
+const INVALID: &str = "./tests/resources/wc/inputs/invalid.txt";
+const LOCALIZED: &str = "./tests/resources/wc/inputs/localized.txt";
+const FILES0: &str = "./tests/resources/wc/inputs/files0.list";
+const FILES0_EMPTY_NAME: &str = "./tests/resources/wc/inputs/files0-empty-name.list";
 
 // --------------------------------------------------
 #[test]
@@ -266,3 +273,311 @@ fn test_all_bytes_lines() -> Result<()> {
         "tests/resources/wc/expected/all.cl.out",
     )
 }
+
+// --------------------------------------------------
+#[test]
+fn wide_max_line_length() -> Result<()> {
+    run(&["-L", WIDE], "tests/resources/wc/expected/wide.txt.L.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn wide_all_counts() -> Result<()> {
+    run(
+        &["-lwmL", WIDE],
+        "tests/resources/wc/expected/wide.txt.lwmL.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn invalid_chars() -> Result<()> {
+    run(
+        &["--chars", INVALID],
+        "tests/resources/wc/expected/invalid.txt.m.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn invalid_all_counts() -> Result<()> {
+    run(
+        &["-l", "-w", "-m", "--max-line-length", INVALID],
+        "tests/resources/wc/expected/invalid.txt.lwmL.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn invalid_stdin() -> Result<()> {
+    let expected = fs::read_to_string("tests/resources/wc/expected/invalid.txt.stdin.out")?;
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .write_stdin(fs::read(INVALID)?)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_max_line_length() -> Result<()> {
+    run(
+        &["-L", WIDE, INVALID, FOX],
+        "tests/resources/wc/expected/all.L.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bytes_only_from_metadata() -> Result<()> {
+    run(
+        &["--bytes", EMPTY, FOX, ATLAMAL],
+        "tests/resources/wc/expected/all.c.out",
+    )
+}
+
+// --------------------------------------------------
+/// A file past the chunk size is scanned in pieces, which must add up to the same counts as
+/// reading it from stdin in one go
+#[test]
+fn big_file_in_chunks() -> Result<()> {
+    let mut file = NamedTempFile::new()?;
+    let parts = [
+        "word",
+        "日本語",
+        "\t",
+        "  ",
+        "é",
+        " x",
+        "😀",
+        "a longer run of words",
+    ];
+    let mut line = String::new();
+    for i in 0..1_100_000 {
+        line.clear();
+        for j in 0..i % 7 {
+            line.push_str(parts[(i + j) % parts.len()]);
+            line.push(' ');
+        }
+        writeln!(file, "{line}")?;
+    }
+    write!(file, "no newline at the end \u{e6}")?;
+    file.flush()?;
+    let path = file.path().to_str().expect("invalid UTF-8");
+
+    let from_file = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-lwmL", path])
+        .output()?;
+    let from_stdin = Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .arg("-lwmL")
+        .write_stdin(fs::read(path)?)
+        .output()?;
+    assert!(from_file.status.success());
+    let from_file = String::from_utf8(from_file.stdout)?;
+    let from_stdin = String::from_utf8(from_stdin.stdout)?;
+    assert_eq!(
+        from_file.split_whitespace().collect::<Vec<_>>(),
+        from_stdin
+            .split_whitespace()
+            .chain([path])
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .arg(format!("--files0-from={FILES0}"))
+        .assert()
+        .success()
+        .stdout(format!(
+            "  1   9  48 {FOX}\n  4  29 173 {ATLAMAL}\n  5  38 221 total\n"
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from_stdin() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-l", "--files0-from", "-"])
+        .write_stdin(fs::read(FILES0)?)
+        .assert()
+        .success()
+        .stdout(format!("  1 {FOX}\n  4 {ATLAMAL}\n  5 total\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from_empty_name() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-w", "--files0-from", FILES0_EMPTY_NAME])
+        .assert()
+        .success()
+        .stdout(format!(" 9 {FOX}\n 0 {EMPTY}\n 9 total\n"))
+        .stderr(format!(
+            "wc: {FILES0_EMPTY_NAME}:2: invalid zero-length file name\n"
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files0_from_missing() -> Result<()> {
+    let bad = helpers::generate_bad_file();
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .arg(format!("--files0-from={bad}"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match(format!(
+            "wc: cannot open '{bad}' for reading: .* [(]os error 2[)]"
+        ))?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files0_from_and_files() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--files0-from", FILES0, FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--files0-from <F>' cannot be used with '[FILE]...'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn named_stdin() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["-", FOX])
+        .write_stdin(fs::read(FOX)?)
+        .assert()
+        .success()
+        .stdout(format!(
+            "      1       9      48 -\n      1       9      48 {FOX}\n      2      18      96 total\n"
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn redirected_stdin() -> Result<()> {
+    // stdin redirected from a regular file sizes the columns like a named file
+    let output = std::process::Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .arg("-")
+        .stdin(fs::File::open(FOX)?)
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, " 1  9 48 -\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn vertical_tab_separates_words() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .arg("-w")
+        .write_stdin("a\x0bb c\x0cd\n")
+        .assert()
+        .success()
+        .stdout("4\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn total_always() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--total=always", "-l", FOX])
+        .assert()
+        .success()
+        .stdout(format!("1 {FOX}\n1 total\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn total_only() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--total=only", EMPTY, FOX, ATLAMAL])
+        .assert()
+        .success()
+        .stdout("  5  38 221\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn total_never() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(["--total", "never", "-c", FOX, ATLAMAL])
+        .assert()
+        .success()
+        .stdout(format!(" 48 {FOX}\n173 {ATLAMAL}\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_word_mode(args: &[&str], expected: &str) -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg(SUBCMD)
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected.to_string());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn localized_ascii_words() -> Result<()> {
+    run_word_mode(&["-w", LOCALIZED], &format!("6 {LOCALIZED}\n"))
+}
+
+// --------------------------------------------------
+#[test]
+fn localized_unicode_words() -> Result<()> {
+    run_word_mode(
+        &["-w", "--word-mode=unicode", LOCALIZED],
+        &format!("8 {LOCALIZED}\n"),
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn localized_uax29_words() -> Result<()> {
+    run_word_mode(
+        &["-w", "--word-mode", "uax29", LOCALIZED],
+        &format!("12 {LOCALIZED}\n"),
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn localized_uax29_all_counts() -> Result<()> {
+    run_word_mode(
+        &["--word-mode=uax29", LOCALIZED, FOX],
+        &format!("  2  12  66 {LOCALIZED}\n  1   9  48 {FOX}\n  3  21 114 total\n"),
+    )
+}
@@ -17,11 +17,23 @@ pub fn run(command: &str, subcommand: &str, args: &[&str]) -> Result<()> {
 
     let expected_stdout = String::from_utf8(expected.stdout).expect("invalid UTF-8");
     let actual_stdout = String::from_utf8(actual.stdout).expect("invalid UTF-8");
-    assert!(actual.status.success());
+    assert_eq!(expected.status.code(), actual.status.code());
     assert_eq!(expected_stdout.trim_end(), actual_stdout.trim_end());
     Ok(())
 }
 
+/// Compares raw output, for input that isn't valid UTF-8
+pub fn run_bytes(command: &str, subcommand: &str, args: &[&str]) -> Result<()> {
+    let expected = std::process::Command::new(subcommand).args(args).output()?;
+    let actual = Command::cargo_bin(command)?
+        .arg(subcommand)
+        .args(args)
+        .output()?;
+    assert!(actual.status.success());
+    assert_eq!(expected.stdout, actual.stdout);
+    Ok(())
+}
+
 pub fn skips_bad_entry(
     command: &str,
     subcommand: &str,